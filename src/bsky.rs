@@ -1,22 +1,37 @@
+use std::borrow::Cow;
+
+use anyhow::Context;
 use anyhow::bail;
 use serde::Deserialize;
 use ureq::Agent;
+use ureq::RequestBuilder;
+use ureq::typestate::WithoutBody;
 use url::Url;
 
 use crate::Collection;
+use crate::Config;
 use crate::Content;
+use crate::Gallery;
 use crate::Item;
 use crate::Post;
 use crate::PostThread;
+use crate::Quote;
+use crate::Reply;
 use crate::TextType;
-
-const API_BASE: &str = "https://public.api.bsky.app";
+use crate::Timestamp;
+use crate::author_name;
+use crate::labeled_url;
+use crate::process_generic;
 
 #[derive(Debug, PartialEq)]
 enum Path<'a> {
+    Feed { profile: &'a str, feed: &'a str },
+    Hashtag { tag: Cow<'a, str> },
     List { profile: &'a str, list: &'a str },
     Post { profile: &'a str, post: &'a str },
     Profile { profile: &'a str },
+    Search { query: Cow<'a, str> },
+    StarterPack { profile: &'a str, pack: &'a str },
 }
 
 fn parse_path(url: &Url) -> Option<Path<'_>> {
@@ -26,7 +41,20 @@ fn parse_path(url: &Url) -> Option<Path<'_>> {
         .collect();
 
     Some(
-        if path_segments.len() == 4 && path_segments[0] == "profile" && path_segments[2] == "lists"
+        if path_segments.len() == 4 && path_segments[0] == "profile" && path_segments[2] == "feed" {
+            Path::Feed {
+                profile: path_segments[1],
+                feed: path_segments[3],
+            }
+        } else if path_segments.len() == 2 && path_segments[0] == "hashtag" {
+            Path::Hashtag {
+                tag: percent_encoding::percent_decode_str(path_segments[1])
+                    .decode_utf8()
+                    .ok()?,
+            }
+        } else if path_segments.len() == 4
+            && path_segments[0] == "profile"
+            && path_segments[2] == "lists"
         {
             Path::List {
                 profile: path_segments[1],
@@ -44,27 +72,43 @@ fn parse_path(url: &Url) -> Option<Path<'_>> {
             Path::Profile {
                 profile: path_segments[1],
             }
+        } else if path_segments == ["search"] {
+            Path::Search {
+                query: url.query_pairs().find(|(k, _)| k == "q")?.1,
+            }
+        } else if path_segments.len() == 3 && path_segments[0] == "starter-pack" {
+            Path::StarterPack {
+                profile: path_segments[1],
+                pack: path_segments[2],
+            }
         } else {
             return None;
         },
     )
 }
 
-pub(crate) fn process(agent: &Agent, url: &mut Url) -> Option<anyhow::Result<Content>> {
+pub(crate) fn process(
+    agent: &Agent,
+    config: &Config,
+    url: &mut Url,
+) -> Option<anyhow::Result<Content>> {
     let path = parse_path(url)?;
+    let appview = AppView {
+        agent,
+        base: config.get_bsky_appview(),
+    };
 
     Some((|| match path {
+        Path::Feed { profile, feed } => process_feed(&appview, profile, feed),
+
+        Path::Hashtag { tag } => search_posts(&appview, &format!("#{tag}")),
+
         Path::List { profile, list } => {
-            let profile = get_profile(agent, profile)?;
-            let list: GetListResponse = agent
-                .get(format!("{API_BASE}/xrpc/app.bsky.graph.getList"))
-                .query(
-                    "list",
-                    format!("at://{}/app.bsky.graph.list/{}", profile.did, list),
-                )
-                .call()?
-                .body_mut()
-                .read_json()?;
+            let profile = get_profile(&appview, profile)?;
+            let list = get_list(
+                &appview,
+                &format!("at://{}/app.bsky.graph.list/{}", profile.did, list),
+            )?;
 
             Ok(Content::Collection(Collection {
                 title: Some(list.list.name),
@@ -72,24 +116,15 @@ pub(crate) fn process(agent: &Agent, url: &mut Url) -> Option<anyhow::Result<Con
                 items: list
                     .items
                     .into_iter()
-                    .map(|item| Item {
-                        url: format!("https://bsky.app/profile/{}", item.subject.handle),
-                        title: Some(
-                            item.subject
-                                .display_name
-                                .filter(|name| !name.is_empty())
-                                .unwrap_or(item.subject.handle),
-                        ),
-                        description: Some(item.subject.description),
-                    })
+                    .map(|item| item.subject.into())
                     .collect(),
             }))
         }
 
         Path::Post { profile, post } => {
-            let profile = get_profile(agent, profile)?;
-            let thread: GetPostThreadResponse = agent
-                .get(format!("{API_BASE}/xrpc/app.bsky.feed.getPostThread"))
+            let profile = get_profile(&appview, profile)?;
+            let thread: GetPostThreadResponse = appview
+                .get("app.bsky.feed.getPostThread")
                 .query(
                     "uri",
                     format!("at://{}/app.bsky.feed.post/{}", profile.did, post),
@@ -104,6 +139,12 @@ pub(crate) fn process(agent: &Agent, url: &mut Url) -> Option<anyhow::Result<Con
                 PostViewEnum::Blocked(_) => bail!("Post was blocked"),
             };
 
+            if config.get_bsky_direct_media()
+                && let Some(content) = direct_media(agent, &thread_view.post)
+            {
+                return content;
+            }
+
             let mut parents: Vec<_> = thread_view
                 .take_parents()
                 .map(|p| p.post.render())
@@ -112,7 +153,10 @@ pub(crate) fn process(agent: &Agent, url: &mut Url) -> Option<anyhow::Result<Con
 
             let replies: Vec<_> = thread_view
                 .take_replies()
-                .map(|r| r.post.render())
+                .map(|(depth, r)| Reply {
+                    depth,
+                    post: r.post.render(),
+                })
                 .collect();
 
             Ok(Content::Text(TextType::PostThread(PostThread {
@@ -124,10 +168,10 @@ pub(crate) fn process(agent: &Agent, url: &mut Url) -> Option<anyhow::Result<Con
         }
 
         Path::Profile { profile } => {
-            let profile = get_profile(agent, profile)?;
-            let posts: GetAuthorFeedResponse = agent
-                .get(format!("{API_BASE}/xrpc/app.bsky.feed.getAuthorFeed"))
-                .query("actor", profile.did)
+            let profile = get_profile(&appview, profile)?;
+            let posts: GetAuthorFeedResponse = appview
+                .get("app.bsky.feed.getAuthorFeed")
+                .query("actor", &profile.did)
                 .call()?
                 .body_mut()
                 .read_json()?;
@@ -136,22 +180,260 @@ pub(crate) fn process(agent: &Agent, url: &mut Url) -> Option<anyhow::Result<Con
                 title: None,
                 before: vec![],
                 main: Post {
-                    author: profile
-                        .display_name
-                        .filter(|name| !name.is_empty())
-                        .unwrap_or(profile.handle),
+                    author: profile.name(),
                     body: profile.description,
-                    urls: vec![],
+                    ..Post::default()
                 },
-                after: posts.feed.into_iter().map(|p| p.post.render()).collect(),
+                after: posts
+                    .feed
+                    .into_iter()
+                    .map(|p| p.post.render().into())
+                    .collect(),
             })))
         }
+
+        Path::Search { query } => search_posts(&appview, &query),
+
+        Path::StarterPack { profile, pack } => process_starter_pack(&appview, profile, pack),
     })())
 }
 
-fn get_profile(agent: &Agent, profile: &str) -> anyhow::Result<ProfileView> {
-    Ok(agent
-        .get(format!("{API_BASE}/xrpc/app.bsky.actor.getProfile"))
+fn process_feed(appview: &AppView, profile: &str, feed: &str) -> anyhow::Result<Content> {
+    let profile = get_profile(appview, profile)?;
+    let uri = format!("at://{}/app.bsky.feed.generator/{}", profile.did, feed);
+    let generator: GetFeedGeneratorResponse = appview
+        .get("app.bsky.feed.getFeedGenerator")
+        .query("feed", &uri)
+        .call()?
+        .body_mut()
+        .read_json()?;
+    let posts: GetFeedResponse = appview
+        .get("app.bsky.feed.getFeed")
+        .query("feed", &uri)
+        .call()?
+        .body_mut()
+        .read_json()?;
+
+    Ok(Content::Text(TextType::PostThread(PostThread {
+        title: Some(generator.view.display_name),
+        before: vec![],
+        main: Post {
+            author: generator.view.creator.name(),
+            body: generator.view.description.unwrap_or_default(),
+            score: generator.view.like_count,
+            ..Post::default()
+        },
+        after: posts
+            .feed
+            .into_iter()
+            .map(|p| p.post.render().into())
+            .collect(),
+    })))
+}
+
+fn process_starter_pack(appview: &AppView, profile: &str, pack: &str) -> anyhow::Result<Content> {
+    let profile = get_profile(appview, profile)?;
+    let pack: GetStarterPackResponse = appview
+        .get("app.bsky.graph.getStarterPack")
+        .query(
+            "starterPack",
+            format!("at://{}/app.bsky.graph.starterpack/{}", profile.did, pack),
+        )
+        .call()?
+        .body_mut()
+        .read_json()?;
+    let pack = pack.starter_pack;
+
+    let mut items: Vec<_> = pack
+        .feeds
+        .into_iter()
+        .flatten()
+        .map(|feed| Item {
+            url: web_url(&feed.creator.handle, &feed.uri),
+            title: Some(feed.display_name),
+            description: feed.description,
+        })
+        .collect();
+    // The sample of members is incomplete so fetch the whole list.
+    if let Some(list) = pack.list {
+        let list = get_list(appview, &list.uri)?;
+        items.extend(list.items.into_iter().map(|item| item.subject.into()));
+    }
+
+    Ok(Content::Collection(Collection {
+        title: Some(pack.record.name),
+        description: pack.record.description,
+        items,
+    }))
+}
+
+/// Return the content of `post` if it is a single image or a video.
+fn direct_media(agent: &Agent, post: &PostView) -> Option<anyhow::Result<Content>> {
+    Some(match post.embed.as_ref()? {
+        Embed::Images(Images { images }) if images.len() == 1 => Url::parse(&images[0].fullsize)
+            .context("Bluesky returned invalid URL")
+            .and_then(|url| process_generic(agent, &url)),
+        Embed::Images(Images { images }) => images
+            .iter()
+            .map(|i| Url::parse(&i.fullsize))
+            .collect::<Result<Vec<_>, _>>()
+            .context("Bluesky returned invalid URL")
+            .and_then(|urls| {
+                let caption = Some(post.record.text.clone()).filter(|t| !t.is_empty());
+                Gallery::download(agent, &urls, caption)
+            })
+            .map(Content::Gallery),
+        Embed::Video(video) => Url::parse(&video.playlist)
+            .map(Content::Video)
+            .context("Bluesky returned invalid URL"),
+        _ => return None,
+    })
+}
+
+fn search_posts(appview: &AppView, query: &str) -> anyhow::Result<Content> {
+    let results: SearchPostsResponse = appview
+        .get("app.bsky.feed.searchPosts")
+        .query("q", query)
+        .query("sort", "latest")
+        .call()?
+        .body_mut()
+        .read_json()?;
+
+    Ok(Content::Collection(Collection {
+        title: Some(format!("Posts matching {query}")),
+        description: None,
+        items: results
+            .posts
+            .into_iter()
+            .map(|post| {
+                let url = web_url(&post.author.handle, &post.uri);
+                let post = post.render();
+                Item {
+                    title: Some(post.author),
+                    url,
+                    description: Some(post.body),
+                }
+            })
+            .collect(),
+    }))
+}
+
+/// Return the bsky.app URL of the record `uri` created by `handle`.
+fn web_url(handle: &str, uri: &str) -> String {
+    let mut parts = uri.rsplit('/');
+    let rkey = parts.next().unwrap_or_default();
+    let collection = parts.next().unwrap_or_default();
+    let kind = match collection {
+        "app.bsky.feed.generator" => "feed",
+        "app.bsky.graph.list" => "lists",
+        _ => "post",
+    };
+    format!("https://bsky.app/profile/{handle}/{kind}/{rkey}")
+}
+
+/// Convert an AT URI, DID, or `@`-prefixed handle to the equivalent bsky.app URL. Returns `None`
+/// if `input` is none of those.
+pub(crate) fn identifier_url(input: &str) -> Option<anyhow::Result<Url>> {
+    let (authority, path) = if let Some(uri) = input.strip_prefix("at://") {
+        // Any query or fragment doesn't change which record is meant.
+        let uri = uri.split(['?', '#']).next().unwrap_or_default();
+        uri.split_once('/').unwrap_or((uri, ""))
+    } else if input.starts_with("did:") {
+        (input, "")
+    } else if let Some(handle) = input.strip_prefix('@')
+        && is_handle(handle)
+    {
+        (handle, "")
+    } else {
+        // A bare domain name is more likely a website than a handle.
+        return None;
+    };
+
+    let path_segments: Vec<_> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let path = match path_segments[..] {
+        [] | ["app.bsky.actor.profile", "self"] => String::new(),
+        ["app.bsky.feed.generator", rkey] => format!("/feed/{rkey}"),
+        ["app.bsky.feed.post", rkey] => format!("/post/{rkey}"),
+        ["app.bsky.graph.list", rkey] => format!("/lists/{rkey}"),
+        ["app.bsky.graph.starterpack", rkey] => {
+            return Some(
+                Url::parse(&format!("https://bsky.app/starter-pack/{authority}/{rkey}"))
+                    .context("Invalid AT URI"),
+            );
+        }
+        [collection, _] => {
+            return Some(Err(anyhow::anyhow!(
+                "Records in {collection} are not supported"
+            )));
+        }
+        _ => return Some(Err(anyhow::anyhow!("Unsupported AT URI"))),
+    };
+    Some(
+        Url::parse(&format!("https://bsky.app/profile/{authority}{path}"))
+            .context("Invalid AT URI"),
+    )
+}
+
+/// Check `s` has the syntax of a handle, which is a domain name.
+fn is_handle(s: &str) -> bool {
+    let labels: Vec<_> = s.split('.').collect();
+    labels.len() >= 2
+        && labels.iter().all(|l| {
+            (1..=63).contains(&l.len())
+                && l.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+                && !l.starts_with('-')
+                && !l.ends_with('-')
+        })
+        && !labels[labels.len() - 1].starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// An `Agent` paired with the base URL of the Bluesky API.
+#[derive(Debug)]
+struct AppView<'a> {
+    agent: &'a Agent,
+    base: &'a str,
+}
+
+impl AppView<'_> {
+    fn get(&self, method: &str) -> RequestBuilder<WithoutBody> {
+        self.agent
+            .get(format!("{}/xrpc/{method}", self.base.trim_end_matches('/')))
+    }
+}
+
+/// Return the list `uri` with all of its items, following the cursor through every page.
+fn get_list(appview: &AppView, uri: &str) -> anyhow::Result<GetListResponse> {
+    // Lists can be huge, so give up on pathologically long ones.
+    const MAX_PAGES: usize = 20;
+
+    let mut list: GetListResponse = appview
+        .get("app.bsky.graph.getList")
+        .query("list", uri)
+        .query("limit", "100")
+        .call()?
+        .body_mut()
+        .read_json()?;
+    for _ in 1..MAX_PAGES {
+        let Some(cursor) = list.cursor.take() else {
+            break;
+        };
+        let page: GetListResponse = appview
+            .get("app.bsky.graph.getList")
+            .query("list", uri)
+            .query("limit", "100")
+            .query("cursor", cursor)
+            .call()?
+            .body_mut()
+            .read_json()?;
+        list.items.extend(page.items);
+        list.cursor = page.cursor;
+    }
+    Ok(list)
+}
+
+fn get_profile(appview: &AppView, profile: &str) -> anyhow::Result<ProfileView> {
+    Ok(appview
+        .get("app.bsky.actor.getProfile")
         .query("actor", profile)
         .call()?
         .body_mut()
@@ -163,10 +445,21 @@ struct GetAuthorFeedResponse {
     feed: Vec<FeedViewPost>,
 }
 
+#[derive(Debug, Deserialize)]
+struct GetFeedGeneratorResponse {
+    view: GeneratorView,
+}
+
+#[derive(Debug, Deserialize)]
+struct GetFeedResponse {
+    feed: Vec<FeedViewPost>,
+}
+
 #[derive(Debug, Deserialize)]
 struct GetListResponse {
     list: ListView,
     items: Vec<ListItemView>,
+    cursor: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -174,11 +467,22 @@ struct GetPostThreadResponse {
     thread: PostViewEnum,
 }
 
+#[derive(Debug, Deserialize)]
+struct GetStarterPackResponse {
+    #[serde(rename = "starterPack")]
+    starter_pack: StarterPackView,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchPostsResponse {
+    posts: Vec<PostView>,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(tag = "$type")]
 enum PostViewEnum {
     #[serde(rename = "app.bsky.feed.defs#threadViewPost")]
-    Thread(ThreadViewPost),
+    Thread(Box<ThreadViewPost>),
     #[serde(rename = "app.bsky.feed.defs#notFoundPost")]
     NotFound(Ignore),
     #[serde(rename = "app.bsky.feed.defs#blockedPost")]
@@ -196,9 +500,29 @@ struct ProfileView {
     handle: String,
     #[serde(rename = "displayName")]
     display_name: Option<String>,
+    #[serde(default)]
     description: String,
 }
 
+impl ProfileView {
+    fn name(&self) -> String {
+        author_name(
+            self.display_name.as_deref().unwrap_or_default(),
+            &self.handle,
+        )
+    }
+}
+
+impl From<ProfileView> for Item {
+    fn from(profile: ProfileView) -> Self {
+        Self {
+            url: format!("https://bsky.app/profile/{}", profile.handle),
+            title: Some(profile.name()),
+            description: Some(profile.description),
+        }
+    }
+}
+
 // app.bsky.actor.defs#profileViewBasic
 #[derive(Debug, Deserialize)]
 struct ProfileViewBasic {
@@ -207,16 +531,46 @@ struct ProfileViewBasic {
     display_name: Option<String>,
 }
 
+impl ProfileViewBasic {
+    fn name(&self) -> String {
+        author_name(
+            self.display_name.as_deref().unwrap_or_default(),
+            &self.handle,
+        )
+    }
+}
+
 // app.bsky.embed.external#view
 #[derive(Debug, Deserialize)]
 struct External {
     external: ViewExternal,
 }
 
+impl External {
+    /// Add the link to the URLs of `post` and any description of it to its body.
+    fn render(self, post: &mut Post) {
+        let ViewExternal {
+            uri,
+            title,
+            description,
+        } = self.external;
+        if !description.is_empty() {
+            if !post.body.is_empty() {
+                post.body.push_str("\n\n");
+            }
+            post.body.push_str(&description);
+        }
+        post.urls.push(labeled_url(&title, uri));
+    }
+}
+
 // app.bsky.embed.external#viewExternal
 #[derive(Debug, Deserialize)]
 struct ViewExternal {
     uri: String,
+    title: String,
+    #[serde(default)]
+    description: String,
 }
 
 // app.bsky.embed.images#view
@@ -225,19 +579,88 @@ struct Images {
     images: Vec<ViewImage>,
 }
 
+impl Images {
+    fn urls(self) -> impl Iterator<Item = String> {
+        self.images
+            .into_iter()
+            .map(|i| labeled_url(&i.alt, i.fullsize))
+    }
+}
+
 // app.bsky.embed.images#viewImage
 #[derive(Debug, Deserialize)]
 struct ViewImage {
     fullsize: String,
+    alt: String,
+}
+
+// app.bsky.embed.defs#aspectRatio
+#[derive(Debug, Deserialize)]
+struct AspectRatio {
+    width: u64,
+    height: u64,
 }
 
 // app.bsky.embed.record#view
 #[derive(Debug, Deserialize)]
-struct EmbedRecord {}
+struct EmbedRecord {
+    record: EmbedRecordView,
+}
+
+impl EmbedRecord {
+    /// Set the quote of `post` to the quoted record.
+    fn render(self, post: &mut Post) {
+        post.quote = Some(match self.record {
+            EmbedRecordView::Record(r) => Quote::Post(Box::new(r.render())),
+            EmbedRecordView::NotFound(_) => Quote::Unavailable("quoted post not found"),
+            EmbedRecordView::Blocked(_) => Quote::Unavailable("quoted post blocked"),
+            EmbedRecordView::Detached(_) => Quote::Unavailable("quoted post removed by its author"),
+            EmbedRecordView::Other => return,
+        });
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "$type")]
+enum EmbedRecordView {
+    #[serde(rename = "app.bsky.embed.record#viewRecord")]
+    Record(Box<ViewRecord>),
+    #[serde(rename = "app.bsky.embed.record#viewNotFound")]
+    NotFound(Ignore),
+    #[serde(rename = "app.bsky.embed.record#viewBlocked")]
+    Blocked(Ignore),
+    #[serde(rename = "app.bsky.embed.record#viewDetached")]
+    Detached(Ignore),
+    // Feed generators, lists, and other records that aren't posts
+    #[serde(other)]
+    Other,
+}
+
+// app.bsky.embed.record#viewRecord
+#[derive(Debug, Deserialize)]
+struct ViewRecord {
+    author: ProfileViewBasic,
+    value: BskyPost,
+    embeds: Option<Vec<Embed>>,
+    #[serde(rename = "likeCount")]
+    like_count: Option<i64>,
+}
+
+impl ViewRecord {
+    fn render(self) -> Post {
+        render_post(
+            &self.author,
+            self.value,
+            self.embeds.into_iter().flatten(),
+            self.like_count,
+        )
+    }
+}
 
 // app.bsky.embed.recordWithMedia#view
 #[derive(Debug, Deserialize)]
 struct RecordWithMedia {
+    record: EmbedRecord,
     media: Media,
 }
 
@@ -248,12 +671,40 @@ enum Media {
     Images(Images),
     #[serde(rename = "app.bsky.embed.external#view")]
     External(External),
+    #[serde(rename = "app.bsky.embed.video#view")]
+    Video(Video),
+}
+
+impl Media {
+    fn render(self, post: &mut Post) {
+        match self {
+            Self::External(e) => e.render(post),
+            Self::Images(i) => post.urls.extend(i.urls()),
+            Self::Video(v) => post.urls.push(v.url()),
+        }
+    }
 }
 
 // app.bsky.embed.video#view
 #[derive(Debug, Deserialize)]
 struct Video {
     playlist: String,
+    alt: Option<String>,
+    #[serde(rename = "aspectRatio")]
+    aspect_ratio: Option<AspectRatio>,
+}
+
+impl Video {
+    fn url(self) -> String {
+        let mut label = self.alt.unwrap_or_default();
+        // The aspect ratio alone would read as if it were the description.
+        if !label.trim().is_empty()
+            && let Some(aspect_ratio) = self.aspect_ratio
+        {
+            label = format!("{label} ({}x{})", aspect_ratio.width, aspect_ratio.height);
+        }
+        labeled_url(&label, self.playlist)
+    }
 }
 
 // app.bsky.feed.defs#feedViewPost
@@ -262,41 +713,68 @@ struct FeedViewPost {
     post: PostView,
 }
 
+// app.bsky.feed.defs#generatorView
+#[derive(Debug, Deserialize)]
+struct GeneratorView {
+    uri: String,
+    creator: ProfileView,
+    #[serde(rename = "displayName")]
+    display_name: String,
+    description: Option<String>,
+    #[serde(rename = "likeCount")]
+    like_count: Option<i64>,
+}
+
 // app.bsky.feed.defs#postView
 #[derive(Debug, Deserialize)]
 struct PostView {
+    uri: String,
     author: ProfileViewBasic,
     record: BskyPost,
     embed: Option<Embed>,
+    #[serde(rename = "likeCount")]
+    like_count: Option<i64>,
 }
 
 impl PostView {
     fn render(self) -> Post {
-        let mut urls: Vec<_> = self
-            .record
-            .facets
-            .into_iter()
-            .flatten()
-            .flat_map(|f| f.features)
-            .filter_map(|f| {
-                if let FaucetFeature::Link(link) = f {
-                    Some(link.uri)
-                } else {
-                    None
-                }
-            })
-            .collect();
-        urls.extend(self.embed.map(Embed::urls).unwrap_or_default());
-        Post {
-            author: self
-                .author
-                .display_name
-                .filter(|name| !name.is_empty())
-                .unwrap_or(self.author.handle),
-            body: self.record.text,
-            urls,
-        }
+        render_post(&self.author, self.record, self.embed, self.like_count)
+    }
+}
+
+fn render_post(
+    author: &ProfileViewBasic,
+    record: BskyPost,
+    embeds: impl IntoIterator<Item = Embed>,
+    like_count: Option<i64>,
+) -> Post {
+    let urls = record
+        .facets
+        .into_iter()
+        .flatten()
+        .flat_map(|f| f.features)
+        .filter_map(|f| {
+            if let FaucetFeature::Link(link) = f {
+                Some(link.uri)
+            } else {
+                None
+            }
+        })
+        .collect();
+    let mut post = Post {
+        author: author.name(),
+        body: record.text,
+        urls,
+        timestamp: Some(record.created_at),
+        score: like_count,
+        edited: false,
+        labels: vec![],
+        quote: None,
+    };
+    for embed in embeds {
+        embed.render(&mut post);
     }
+    post
 }
 
 #[derive(Debug, Deserialize)]
@@ -315,16 +793,16 @@ enum Embed {
 }
 
 impl Embed {
-    fn urls(self) -> Vec<String> {
+    fn render(self, post: &mut Post) {
         match self {
-            Self::External(e) => vec![e.external.uri],
-            Self::Images(i) => i.images.into_iter().map(|i| i.fullsize).collect(),
-            Self::Record(_) => vec![],
-            Self::RecordWithMedia(r) => match r.media {
-                Media::External(e) => vec![e.external.uri],
-                Media::Images(i) => i.images.into_iter().map(|i| i.fullsize).collect(),
-            },
-            Self::Video(v) => vec![v.playlist],
+            Self::External(e) => e.render(post),
+            Self::Images(i) => post.urls.extend(i.urls()),
+            Self::Record(r) => r.render(post),
+            Self::RecordWithMedia(r) => {
+                r.media.render(post);
+                r.record.render(post);
+            }
+            Self::Video(v) => post.urls.push(v.url()),
         }
     }
 }
@@ -360,7 +838,7 @@ impl Iterator for TakeParents {
 
     fn next(&mut self) -> Option<Self::Item> {
         let mut item = match *self.next.take()? {
-            PostViewEnum::Thread(v) => Some(v),
+            PostViewEnum::Thread(v) => Some(*v),
             PostViewEnum::NotFound(_) | PostViewEnum::Blocked(_) => None,
         }?;
         self.next = item.parent.take();
@@ -373,15 +851,16 @@ struct TakeReplies {
 }
 
 impl Iterator for TakeReplies {
-    type Item = ThreadViewPost;
+    type Item = (usize, ThreadViewPost);
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
             for item in self.stack.last_mut()? {
                 if let PostViewEnum::Thread(mut thread) = item {
+                    let depth = self.stack.len() - 1;
                     self.stack
                         .push(thread.replies.take().unwrap_or_default().into_iter());
-                    return Some(thread);
+                    return Some((depth, *thread));
                 }
             }
             self.stack.pop();
@@ -394,6 +873,8 @@ impl Iterator for TakeReplies {
 struct BskyPost {
     text: String,
     facets: Option<Vec<Facet>>,
+    #[serde(rename = "createdAt")]
+    created_at: Timestamp,
 }
 
 // app.bsky.graph.defs#listItemView
@@ -409,6 +890,27 @@ struct ListView {
     description: Option<String>,
 }
 
+// app.bsky.graph.defs#listViewBasic
+#[derive(Debug, Deserialize)]
+struct ListViewBasic {
+    uri: String,
+}
+
+// app.bsky.graph.defs#starterPackView
+#[derive(Debug, Deserialize)]
+struct StarterPackView {
+    record: StarterPack,
+    list: Option<ListViewBasic>,
+    feeds: Option<Vec<GeneratorView>>,
+}
+
+// app.bsky.graph.starterpack
+#[derive(Debug, Deserialize)]
+struct StarterPack {
+    name: String,
+    description: Option<String>,
+}
+
 // app.bsky.richtext.facet
 #[derive(Debug, Deserialize)]
 struct Facet {
@@ -437,11 +939,125 @@ struct FacetLink {
 #[cfg(test)]
 mod tests {
     use super::Path;
+    use super::PostView;
+    use super::identifier_url;
+    use super::web_url;
     use crate::tests::parse_path_tests;
 
+    fn post_view(embed: &str) -> PostView {
+        serde_json::from_str(&format!(
+            r#"{{
+                "uri": "at://did:plc:a/app.bsky.feed.post/3abc",
+                "author": {{"handle": "a.example.com", "displayName": "A"}},
+                "record": {{"text": "Look at this", "createdAt": "2024-01-02T03:04:05Z"}},
+                "embed": {embed}
+            }}"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn quote_post() {
+        let post = post_view(
+            r#"{
+                "$type": "app.bsky.embed.record#view",
+                "record": {
+                    "$type": "app.bsky.embed.record#viewRecord",
+                    "author": {"handle": "b.example.com"},
+                    "value": {"text": "Original\n\nSecond", "createdAt": "2024-01-01T00:00:00Z"}
+                }
+            }"#,
+        )
+        .render();
+        assert_eq!(post.body, "Look at this");
+        assert_eq!(
+            post.to_string(),
+            "<A (@a.example.com)> [2024-01-02 03:04 UTC] Look at this\n\n\
+             > <@b.example.com> [2024-01-01 00:00 UTC] Original\n>\n> Second"
+        );
+    }
+
+    #[test]
+    fn quote_post_with_media() {
+        let post = post_view(
+            r#"{
+                "$type": "app.bsky.embed.recordWithMedia#view",
+                "record": {"record": {"$type": "app.bsky.embed.record#viewBlocked"}},
+                "media": {
+                    "$type": "app.bsky.embed.images#view",
+                    "images": [{"fullsize": "https://example.com/a.jpg", "alt": "A cat\nsleeping"}]
+                }
+            }"#,
+        )
+        .render();
+        assert_eq!(
+            post.to_string(),
+            "<A (@a.example.com)> [2024-01-02 03:04 UTC] Look at this\n\n\
+             > [quoted post blocked]\n\nA cat sleeping: https://example.com/a.jpg"
+        );
+        assert_eq!(post.urls, ["A cat sleeping: https://example.com/a.jpg"]);
+    }
+
+    #[test]
+    fn media_labels() {
+        let post = post_view(
+            r#"{
+                "$type": "app.bsky.embed.external#view",
+                "external": {"uri": "https://example.com/", "title": "Example", "description": ""}
+            }"#,
+        )
+        .render();
+        assert_eq!(post.body, "Look at this");
+        assert_eq!(post.urls, ["Example: https://example.com/"]);
+
+        let post = post_view(
+            r#"{
+                "$type": "app.bsky.embed.external#view",
+                "external": {
+                    "uri": "https://example.com/",
+                    "title": "Example",
+                    "description": "An example page"
+                }
+            }"#,
+        )
+        .render();
+        assert_eq!(post.body, "Look at this\n\nAn example page");
+        assert_eq!(post.urls, ["Example: https://example.com/"]);
+
+        let post = post_view(
+            r#"{
+                "$type": "app.bsky.embed.video#view",
+                "playlist": "https://example.com/v.m3u8",
+                "aspectRatio": {"width": 16, "height": 9}
+            }"#,
+        )
+        .render();
+        assert_eq!(post.urls, ["https://example.com/v.m3u8"]);
+
+        let post = post_view(
+            r#"{
+                "$type": "app.bsky.embed.video#view",
+                "playlist": "https://example.com/v.m3u8",
+                "alt": "A dog",
+                "aspectRatio": {"width": 16, "height": 9}
+            }"#,
+        )
+        .render();
+        assert_eq!(post.urls, ["A dog (16x9): https://example.com/v.m3u8"]);
+    }
+
     parse_path_tests!(
         super::parse_path,
         "https://bsky.app{}",
+        (
+            feed,
+            "/profile/example.bsky.social/feed/aaabbb",
+            Some(Path::Feed {
+                profile: "example.bsky.social",
+                feed: "aaabbb"
+            })
+        ),
+        (hashtag, "/hashtag/caf%C3%A9", Some(Path::Hashtag { tag }) if tag == "café"),
         (
             list,
             "/profile/example.bsky.social/lists/17296c1",
@@ -465,6 +1081,112 @@ mod tests {
                 profile: "example.bsky.social"
             })
         ),
+        (
+            did_post,
+            "/profile/did:plc:abc/post/3xyz",
+            Some(Path::Post {
+                profile: "did:plc:abc",
+                post: "3xyz"
+            })
+        ),
+        (
+            did_profile,
+            "/profile/did:web:example.com",
+            Some(Path::Profile {
+                profile: "did:web:example.com"
+            })
+        ),
+        (
+            other_collection,
+            "/profile/did:plc:abc/com.example.thing/3xyz",
+            None
+        ),
+        (search, "/search?q=rust+lang", Some(Path::Search { query }) if query == "rust lang"),
+        (search_no_query, "/search", None),
+        (
+            starter_pack,
+            "/starter-pack/example.bsky.social/3abc",
+            Some(Path::StarterPack {
+                profile: "example.bsky.social",
+                pack: "3abc"
+            })
+        ),
         (unknown, "/unknown", None),
     );
+
+    macro_rules! identifier_tests {
+        ($(($name: ident, $input: expr, $expected: expr),)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    assert_eq!(
+                        identifier_url($input).map(|r| r.map(String::from).ok()),
+                        $expected.map(|e: Option<&str>| e.map(str::to_owned))
+                    );
+                }
+            )*
+        }
+    }
+
+    identifier_tests!(
+        (
+            at_post,
+            "at://did:plc:abc/app.bsky.feed.post/3xyz",
+            Some(Some("https://bsky.app/profile/did:plc:abc/post/3xyz"))
+        ),
+        (
+            at_profile,
+            "at://example.bsky.social",
+            Some(Some("https://bsky.app/profile/example.bsky.social"))
+        ),
+        (
+            at_profile_record,
+            "at://did:plc:abc/app.bsky.actor.profile/self",
+            Some(Some("https://bsky.app/profile/did:plc:abc"))
+        ),
+        (
+            at_starter_pack,
+            "at://did:plc:abc/app.bsky.graph.starterpack/3xyz",
+            Some(Some("https://bsky.app/starter-pack/did:plc:abc/3xyz"))
+        ),
+        (
+            at_other,
+            "at://did:plc:abc/com.example.thing/3xyz",
+            Some(None)
+        ),
+        (
+            at_collection,
+            "at://did:plc:abc/app.bsky.feed.post",
+            Some(None)
+        ),
+        (
+            did,
+            "did:web:example.com",
+            Some(Some("https://bsky.app/profile/did:web:example.com"))
+        ),
+        (
+            handle,
+            "@example.bsky.social",
+            Some(Some("https://bsky.app/profile/example.bsky.social"))
+        ),
+        (bare_handle, "example.bsky.social", None),
+        (url, "https://bsky.app/", None),
+        (ip_address, "@127.0.0.1", None),
+        (single_label, "@localhost", None),
+    );
+
+    #[test]
+    fn web_urls() {
+        assert_eq!(
+            web_url("a.example.com", "at://did:plc:x/app.bsky.feed.post/3abc"),
+            "https://bsky.app/profile/a.example.com/post/3abc"
+        );
+        assert_eq!(
+            web_url(
+                "a.example.com",
+                "at://did:plc:x/app.bsky.feed.generator/news"
+            ),
+            "https://bsky.app/profile/a.example.com/feed/news"
+        );
+    }
 }
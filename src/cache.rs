@@ -0,0 +1,65 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use tempfile::NamedTempFile;
+
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Stop reading and writing cache files, such as when filesystem access is restricted.
+pub(crate) fn disable() {
+    DISABLED.store(true, Ordering::Relaxed);
+}
+
+/// Return whether cache files are read and written.
+pub(crate) fn enabled() -> bool {
+    !DISABLED.load(Ordering::Relaxed)
+}
+
+/// Return the directory for cached data following the XDG Base Directory Specification.
+fn cache_dir() -> Option<PathBuf> {
+    if !enabled() {
+        return None;
+    }
+    env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .filter(|d| d.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|h| PathBuf::from(h).join(".cache")))
+        .map(|d| d.join("zxcv"))
+}
+
+/// Return the contents of the cache file `name` or `None` if it does not exist or is unreadable.
+pub(crate) fn read(name: &str) -> Option<String> {
+    fs::read_to_string(cache_dir()?.join(name)).ok()
+}
+
+/// Return how long ago the cache file `name` was written or `None` if it does not exist.
+pub(crate) fn age(name: &str) -> Option<Duration> {
+    fs::metadata(cache_dir()?.join(name))
+        .and_then(|m| m.modified())
+        .ok()?
+        .elapsed()
+        .ok()
+}
+
+/// Replace the contents of the cache file `name` with `contents`.
+pub(crate) fn write(name: &str, contents: &str) -> io::Result<()> {
+    let Some(dir) = cache_dir() else {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "No cache directory available",
+        ));
+    };
+    fs::create_dir_all(&dir)?;
+
+    // Write to a temporary file first so a concurrent reader never sees a partial file.
+    let mut file = NamedTempFile::new_in(&dir)?;
+    file.write_all(contents.as_bytes())?;
+    file.persist(dir.join(name))?;
+    Ok(())
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use crate::Content;
 
 use serde::Deserialize;
@@ -19,6 +21,7 @@ use serde::Deserialize;
 /// | --- | ------- |
 /// | audio | `["mpv", "--profile=builtin-pseudo-gui", "--", "%u"]` |
 /// | collection | `["xterm", "-e", "%p", "--", "%f"]` |
+/// | gallery | `["nsxiv", "--", "%F"]` |
 /// | image | `["mupdf", "--", "%f"]` |
 /// | pdf | `["mupdf", "--", "%f", "%p"]` |
 /// | text | `["xterm", "-e", "%p", "--", "%f"]` |
@@ -32,16 +35,52 @@ use serde::Deserialize;
 /// | Audio | `%u` | URL of the audio. |
 /// | Collection | `%f` | Filename of a temporary file containing links to the items of the collection. |
 /// | Collection | `%p` | Value of the `PAGER` environment variable or an empty string if unset. |
+/// | Gallery | `%c` | Caption of the images if any. |
+/// | Gallery | `%F` | Filenames of temporary files containing the images, each a separate argument. |
 /// | Image | `%f` | Filename of a temporary file containing the image. |
 /// | PDF | `%f` | Filename of a temporary file containing the PDF. |
 /// | PDF | `%p` | Page of the PDF if specified. |
 /// | Text | `%f` | Filename of a temporary file containing the text. |
 /// | Text | `%p` | Value of the `PAGER` environment variable or an empty string if unset. |
 /// | Video | `%u` | URL of the video. |
+///
+/// # `[bsky]`
+///
+/// | Key | Default | Description |
+/// | --- | ------- | ----------- |
+/// | `appview` | `"https://public.api.bsky.app"` | Base URL of the Bluesky API. |
+/// | `direct_media` | `false` | Show a post of images or a video as those images or that video rather than as text. |
+/// | `frontends` | `[]` | Hostnames of other Bluesky web frontends whose URLs to show like bsky.app's, e.g. `["bsky.example.com"]`. Links in the output still point to bsky.app. |
+///
+/// # `[menu]`
+///
+/// | Key | Default | Description |
+/// | --- | ------- | ----------- |
+/// | `argv` | `[]` | Command to choose an item of a collection or a link, e.g. `["fzf"]`. It is given numbered items one per line on standard input and must write the chosen line to standard output. If empty, the items are listed on the terminal with a prompt for the number. |
+/// | `interactive` | `false` | Choose an item of a collection to show rather than showing the collection. |
+/// | `links` | `false` | After showing content, choose one of the URLs in it to show. |
+///
+/// # `[stackexchange]`
+///
+/// The stackexchange section adds hosts serving the Stack Exchange API beyond those listed by
+/// `api.stackexchange.com`. Each key is a hostname and each value is a table with these keys.
+///
+/// | Key | Default | Description |
+/// | --- | ------- | ----------- |
+/// | site | | The `site` parameter to pass to the API. |
+/// | api | `"https://api.stackexchange.com/2.3"` | Base URL of the API. |
+///
+/// ```toml
+/// [stackexchange]
+/// "qa.example.com" = { site = "qa", api = "https://qa.example.com/api/2.3" }
+/// ```
 #[derive(Debug, Default, Deserialize, PartialEq)]
 #[serde(default, deny_unknown_fields)]
 pub struct Config {
     argv: Argv,
+    bsky: Bsky,
+    menu: Menu,
+    stackexchange: HashMap<String, StackExchangeSite>,
 }
 
 #[derive(Debug, Deserialize, PartialEq)]
@@ -49,12 +88,46 @@ pub struct Config {
 struct Argv {
     audio: Vec<String>,
     collection: Vec<String>,
+    gallery: Vec<String>,
     image: Vec<String>,
     pdf: Vec<String>,
     text: Vec<String>,
     video: Vec<String>,
 }
 
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+struct Bsky {
+    appview: String,
+    direct_media: bool,
+    frontends: Vec<String>,
+}
+
+impl Default for Bsky {
+    fn default() -> Self {
+        Self {
+            appview: "https://public.api.bsky.app".to_owned(),
+            direct_media: false,
+            frontends: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+struct Menu {
+    argv: Vec<String>,
+    interactive: bool,
+    links: bool,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct StackExchangeSite {
+    pub(crate) site: String,
+    pub(crate) api: Option<String>,
+}
+
 impl Default for Argv {
     fn default() -> Self {
         Self {
@@ -66,6 +139,10 @@ impl Default for Argv {
                 .iter()
                 .map(|&s| s.to_owned())
                 .collect(),
+            gallery: ["nsxiv", "--", "%F"]
+                .iter()
+                .map(|&s| s.to_owned())
+                .collect(),
             image: ["mupdf", "--", "%f"]
                 .iter()
                 .map(|&s| s.to_owned())
@@ -105,16 +182,77 @@ impl Config {
         toml::from_str(config)
     }
 
+    /// Set whether to choose an item of a collection to show rather than showing the collection,
+    /// overriding the configuration file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use zxcv::Config;
+    /// #
+    /// let mut config = Config::default();
+    /// config.set_interactive(true);
+    /// ```
+    #[inline]
+    pub fn set_interactive(&mut self, interactive: bool) {
+        self.menu.interactive = interactive;
+    }
+
+    /// Set whether to choose one of the URLs in content to show after showing it, overriding the
+    /// configuration file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use zxcv::Config;
+    /// #
+    /// let mut config = Config::default();
+    /// config.set_links(true);
+    /// ```
+    #[inline]
+    pub fn set_links(&mut self, links: bool) {
+        self.menu.links = links;
+    }
+
     pub(crate) fn get_argv(&self, content: &Content) -> &[String] {
         match content {
             Content::Audio(_) => &self.argv.audio,
             Content::Collection(_) => &self.argv.collection,
+            Content::Gallery(_) => &self.argv.gallery,
             Content::Image(_) => &self.argv.image,
             Content::Pdf(..) => &self.argv.pdf,
             Content::Text(_) => &self.argv.text,
             Content::Video(_) => &self.argv.video,
         }
     }
+
+    pub(crate) fn get_bsky_appview(&self) -> &str {
+        &self.bsky.appview
+    }
+
+    pub(crate) fn is_bsky_frontend(&self, hostname: &str) -> bool {
+        self.bsky.frontends.iter().any(|h| h == hostname)
+    }
+
+    pub(crate) fn get_bsky_direct_media(&self) -> bool {
+        self.bsky.direct_media
+    }
+
+    pub(crate) fn get_menu_argv(&self) -> &[String] {
+        &self.menu.argv
+    }
+
+    pub(crate) fn is_interactive(&self) -> bool {
+        self.menu.interactive
+    }
+
+    pub(crate) fn is_links(&self) -> bool {
+        self.menu.links
+    }
+
+    pub(crate) fn get_stackexchange_site(&self, hostname: &str) -> Option<&StackExchangeSite> {
+        self.stackexchange.get(hostname)
+    }
 }
 
 #[cfg(test)]
@@ -133,4 +271,47 @@ mod tests {
         assert!(Config::from_toml("[argv]\ntext = \"baz\"\n").is_err());
         assert!(Config::from_toml("[argv]\ntext = [\"baz\"]\n").is_ok());
     }
+
+    #[test]
+    fn bsky() {
+        let config = Config::default();
+        assert_eq!(config.get_bsky_appview(), "https://public.api.bsky.app");
+        assert!(!config.is_bsky_frontend("bsky.example.com"));
+
+        let config = Config::from_toml(
+            "[bsky]\nappview = \"http://localhost:2584\"\nfrontends = [\"bsky.example.com\"]\n",
+        )
+        .unwrap();
+        assert_eq!(config.get_bsky_appview(), "http://localhost:2584");
+        assert!(config.is_bsky_frontend("bsky.example.com"));
+        assert!(!config.get_bsky_direct_media());
+    }
+
+    #[test]
+    fn menu() {
+        let mut config = Config::from_toml("[menu]\nargv = [\"fzf\"]\n").unwrap();
+        assert_eq!(config.get_menu_argv(), ["fzf"]);
+        assert!(!config.is_interactive());
+        config.set_interactive(true);
+        assert!(config.is_interactive());
+        assert!(!config.is_links());
+        config.set_links(true);
+        assert!(config.is_links());
+    }
+
+    #[test]
+    fn stackexchange_sites() {
+        let config = Config::from_toml(
+            "[stackexchange]\n\"qa.example.com\" = { site = \"qa\", api = \"https://qa.example.com/api\" }\n",
+        )
+        .unwrap();
+        let site = config.get_stackexchange_site("qa.example.com").unwrap();
+        assert_eq!(site.site, "qa");
+        assert_eq!(site.api.as_deref(), Some("https://qa.example.com/api"));
+        assert!(config.get_stackexchange_site("example.com").is_none());
+
+        assert!(
+            Config::from_toml("[stackexchange]\n\"qa.example.com\" = { api = \"x\" }\n").is_err()
+        );
+    }
 }
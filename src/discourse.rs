@@ -1,4 +1,3 @@
-use anyhow::bail;
 use scraper::Html;
 use serde::Deserialize;
 use ureq::Agent;
@@ -7,8 +6,16 @@ use url::Url;
 use crate::Content;
 use crate::Post;
 use crate::PostThread;
+use crate::Reply;
 use crate::TextType;
+use crate::Timestamp;
 use crate::html;
+use crate::nest_replies;
+
+/// Return whether `url` is a topic.
+pub(crate) fn supports_path(url: &Url) -> bool {
+    topic_id(url).is_some()
+}
 
 pub(crate) fn try_process(
     agent: &Agent,
@@ -22,44 +29,62 @@ pub(crate) fn try_process(
         return None;
     }
 
-    Some((|| {
-        let path_segments: Vec<_> = url
-            .path_segments()
-            .unwrap_or_else(|| "".split('/'))
-            .collect();
+    process(agent, url)
+}
 
-        if path_segments.len() >= 3 && path_segments[0] == "t" {
-            let mut topic: Topic = agent
-                .get(
-                    url.join(&format!("/t/{}.json", path_segments[2]))
-                        .expect("URL is valid")
-                        .as_str(),
-                )
-                .call()?
-                .body_mut()
-                .read_json()?;
+pub(crate) fn process(agent: &Agent, url: &Url) -> Option<anyhow::Result<Content>> {
+    let topic_id = topic_id(url)?;
 
-            Ok(Content::Text(TextType::PostThread(PostThread {
-                title: Some(topic.title),
-                before: vec![],
-                main: topic.post_stream.posts.remove(0).render(url),
-                after: topic
-                    .post_stream
-                    .posts
-                    .into_iter()
-                    .map(|p| p.render(url))
-                    .collect(),
-            })))
-        } else {
-            bail!("Unknown discourse URL");
-        }
+    Some((|| {
+        let mut topic: Topic = agent
+            .get(
+                url.join(&format!("/t/{topic_id}.json"))
+                    .expect("URL is valid")
+                    .as_str(),
+            )
+            .call()?
+            .body_mut()
+            .read_json()?;
+
+        Ok(Content::Text(TextType::PostThread(PostThread {
+            title: Some(topic.title),
+            before: vec![],
+            main: topic.post_stream.posts.remove(0).render(url),
+            after: nest_replies(
+                topic.post_stream.posts,
+                |p| p.post_number,
+                |p| p.reply_to_post_number,
+            )
+            .into_iter()
+            .map(|(depth, p)| Reply {
+                depth,
+                post: p.render(url),
+            })
+            .collect(),
+        })))
     })())
 }
 
+/// Return the ID of the topic at `url`, which is of the form `/t/<slug>/<id>`.
+fn topic_id(url: &Url) -> Option<&str> {
+    let path_segments: Vec<_> = url
+        .path_segments()
+        .unwrap_or_else(|| "".split('/'))
+        .collect();
+    if path_segments.len() < 3 || path_segments[0] != "t" {
+        return None;
+    }
+    Some(path_segments[2])
+}
+
 #[derive(Debug, Deserialize)]
 struct DiscoursePost {
     cooked: String,
+    created_at: Timestamp,
+    post_number: u64,
+    reply_to_post_number: Option<u64>,
     username: String,
+    version: u64,
 }
 
 impl DiscoursePost {
@@ -68,6 +93,11 @@ impl DiscoursePost {
             author: self.username,
             body: html::render(&self.cooked, url),
             urls: vec![],
+            timestamp: Some(self.created_at),
+            score: None,
+            edited: self.version > 1,
+            labels: vec![],
+            quote: None,
         }
     }
 }
@@ -9,6 +9,7 @@ use crate::Content;
 use crate::Post;
 use crate::PostThread;
 use crate::TextType;
+use crate::Timestamp;
 use crate::html;
 use crate::read_raw_response;
 
@@ -51,6 +52,11 @@ fn parse_path(url: &Url) -> Option<Path<'_>> {
     )
 }
 
+/// Return whether `url` is a commit, issue or source file.
+pub(crate) fn supports_path(url: &Url) -> bool {
+    parse_path(url).is_some()
+}
+
 pub(crate) fn try_process(
     agent: &Agent,
     url: &Url,
@@ -110,8 +116,10 @@ pub(crate) fn process(agent: &Agent, url: &Url) -> Option<anyhow::Result<Content
                     author: issue.user.login,
                     body: issue.body,
                     urls: vec![],
+                    timestamp: Some(issue.created_at),
+                    ..Post::default()
                 },
-                after: comments.into_iter().map(Into::into).collect(),
+                after: comments.into_iter().map(|c| Post::from(c).into()).collect(),
             })))
         }
         Path::Src(owner, repo, filepath, r#ref) => {
@@ -140,6 +148,8 @@ pub(crate) fn process(agent: &Agent, url: &Url) -> Option<anyhow::Result<Content
 #[derive(Debug, Deserialize)]
 struct Comment {
     body: String,
+    created_at: Timestamp,
+    updated_at: Timestamp,
     user: User,
 }
 
@@ -149,6 +159,11 @@ impl From<Comment> for Post {
             author: comment.user.login,
             body: comment.body,
             urls: vec![],
+            timestamp: Some(comment.created_at),
+            score: None,
+            edited: comment.updated_at > comment.created_at,
+            labels: vec![],
+            quote: None,
         }
     }
 }
@@ -162,6 +177,7 @@ struct ContentsResponse {
 #[derive(Debug, Deserialize)]
 struct Issue {
     body: String,
+    created_at: Timestamp,
     title: String,
     user: User,
 }
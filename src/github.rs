@@ -7,6 +7,7 @@ use crate::Content;
 use crate::Post;
 use crate::PostThread;
 use crate::TextType;
+use crate::Timestamp;
 use crate::process_generic;
 use crate::read_raw_response;
 
@@ -122,8 +123,10 @@ pub(crate) fn process(agent: &Agent, url: &mut Url) -> Option<anyhow::Result<Con
                     author: issue.user.login,
                     body: issue.body,
                     urls: vec![],
+                    timestamp: Some(issue.created_at),
+                    ..Post::default()
                 },
-                after: comments.into_iter().map(Into::into).collect(),
+                after: comments.into_iter().map(|c| Post::from(c).into()).collect(),
             })))
         }
         Path::PullRequest(owner, repo_name, pr_id) => {
@@ -134,7 +137,7 @@ pub(crate) fn process(agent: &Agent, url: &mut Url) -> Option<anyhow::Result<Con
             let mut comments: Vec<Comment> = request(agent, &pull_request.comments_url)?;
             let review_comments: Vec<Comment> = request(agent, &pull_request.review_comments_url)?;
             comments.extend(review_comments);
-            comments.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+            comments.sort_by_key(|c| c.created_at);
 
             Ok(Content::Text(TextType::PostThread(PostThread {
                 title: Some(pull_request.title),
@@ -143,8 +146,10 @@ pub(crate) fn process(agent: &Agent, url: &mut Url) -> Option<anyhow::Result<Con
                     author: pull_request.user.login,
                     body: pull_request.body.unwrap_or_default(),
                     urls: vec![pull_request.patch_url],
+                    timestamp: Some(pull_request.created_at),
+                    ..Post::default()
                 },
-                after: comments.into_iter().map(Into::into).collect(),
+                after: comments.into_iter().map(|c| Post::from(c).into()).collect(),
             })))
         }
         Path::Raw(url) => process_generic(agent, url),
@@ -157,6 +162,8 @@ pub(crate) fn process(agent: &Agent, url: &mut Url) -> Option<anyhow::Result<Con
                 author: release.author.login,
                 body: release.body,
                 urls: vec![release.tarball_url],
+                timestamp: release.published_at,
+                ..Post::default()
             })))
         }
         Path::Repo(owner, repo_name) => {
@@ -191,7 +198,8 @@ fn request_raw(agent: &Agent, url: &str) -> anyhow::Result<Vec<u8>> {
 #[derive(Debug, Deserialize)]
 struct Comment {
     body: String,
-    created_at: String, // ISO timestamp
+    created_at: Timestamp,
+    updated_at: Timestamp,
     user: User,
 }
 
@@ -201,6 +209,11 @@ impl From<Comment> for Post {
             author: comment.user.login,
             body: comment.body,
             urls: vec![],
+            timestamp: Some(comment.created_at),
+            score: None,
+            edited: comment.updated_at > comment.created_at,
+            labels: vec![],
+            quote: None,
         }
     }
 }
@@ -208,6 +221,7 @@ impl From<Comment> for Post {
 #[derive(Debug, Deserialize)]
 struct Issue {
     body: String,
+    created_at: Timestamp,
     comments_url: String,
     title: String,
     user: User,
@@ -217,6 +231,7 @@ struct Issue {
 struct PullRequest {
     body: Option<String>,
     comments_url: String,
+    created_at: Timestamp,
     patch_url: String,
     review_comments_url: String,
     title: String,
@@ -227,6 +242,7 @@ struct PullRequest {
 struct Release {
     author: User,
     body: String,
+    published_at: Option<Timestamp>,
     tarball_url: String,
 }
 
@@ -310,12 +326,14 @@ mod tests {
 pub(crate) mod gist {
     use std::collections::HashMap;
 
+    use anyhow::Context;
     use serde::Deserialize;
     use ureq::Agent;
     use url::Url;
 
     use crate::Collection;
     use crate::Content;
+    use crate::Gallery;
     use crate::Item;
     use crate::TextType;
 
@@ -329,6 +347,20 @@ pub(crate) mod gist {
         if gist.files.len() == 1 {
             let file = gist.files.into_values().next().expect("Checked above");
             Ok(Content::Text(TextType::Raw(file.content.into())))
+        } else if gist
+            .files
+            .values()
+            .all(|f| f.r#type.as_deref().is_some_and(|t| t.starts_with("image/")))
+        {
+            let mut files: Vec<_> = gist.files.into_values().collect();
+            files.sort_by(|a, b| a.filename.cmp(&b.filename));
+            let urls = files
+                .iter()
+                .map(|f| Url::parse(&f.raw_url))
+                .collect::<Result<Vec<_>, _>>()
+                .context("GitHub API returned invalid URL")?;
+            let caption = Some(gist.description).filter(|d| !d.is_empty());
+            Ok(Content::Gallery(Gallery::download(agent, &urls, caption)?))
         } else {
             Ok(Content::Collection(Collection {
                 title: None,
@@ -357,5 +389,6 @@ pub(crate) mod gist {
         content: String,
         filename: String,
         raw_url: String,
+        r#type: Option<String>,
     }
 }
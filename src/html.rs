@@ -2,6 +2,9 @@ use std::borrow::Cow;
 use std::num::NonZeroUsize;
 
 use ego_tree::NodeRef;
+use pulldown_cmark::Event;
+use pulldown_cmark::Options;
+use pulldown_cmark::Parser;
 use scraper::CaseSensitivity;
 use scraper::ElementRef;
 use scraper::Html;
@@ -56,6 +59,21 @@ pub(crate) fn select_single_element<'a>(
     }
 }
 
+/// Remove every element matched by `selector` along with its descendants.
+///
+/// # Panics
+///
+/// It is the caller's responsibility to ensure the `selector` is valid.
+pub(crate) fn remove_elements(tree: &mut Html, selector_string: &str) {
+    let selector = Selector::parse(selector_string).expect("Caller must supply a valid selector");
+    let ids: Vec<_> = tree.select(&selector).map(|e| e.id()).collect();
+    for id in ids {
+        if let Some(mut node) = tree.tree.get_mut(id) {
+            node.detach();
+        }
+    }
+}
+
 pub(crate) fn render(html: &str, url: &Url) -> String {
     render_node(
         *Html::parse_fragment(html).root_element(),
@@ -64,6 +82,22 @@ pub(crate) fn render(html: &str, url: &Url) -> String {
     )
 }
 
+/// Render `CommonMark` `markdown` as `render` does its HTML. Raw HTML is shown as text as
+/// servers which take markdown don't allow it either.
+pub(crate) fn render_markdown(markdown: &str, url: &Url) -> String {
+    let parser = Parser::new_ext(
+        markdown,
+        Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TABLES,
+    )
+    .map(|event| match event {
+        Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+        event => event,
+    });
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, parser);
+    render(&html, url)
+}
+
 pub(crate) fn render_node(
     node: NodeRef<'_, Node>,
     url: &Url,
@@ -353,9 +387,15 @@ fn render_node_inner(node: NodeRef<'_, Node>, url: &Url, block: &mut Block) {
 
 #[cfg(test)]
 mod tests {
+    use std::num::NonZeroUsize;
+
+    use scraper::Html;
     use url::Url;
 
+    use super::remove_elements;
     use super::render;
+    use super::render_markdown;
+    use super::render_node;
 
     macro_rules! render_tests {
         ($(($name: ident, $html: expr, $expected: expr),)*) => {
@@ -639,4 +679,34 @@ mod tests {
             "E"
         ),
     );
+
+    #[test]
+    fn remove_elements_nested() {
+        let mut tree = Html::parse_fragment(
+            "<p>foo<sup class=\"reference\">[1]</sup></p><div class=\"navbox\"><p>bar</p></div>baz",
+        );
+        remove_elements(&mut tree, ".reference, .navbox");
+        assert_eq!(
+            render_node(
+                *tree.root_element(),
+                &Url::parse("https://example.com/").unwrap(),
+                NonZeroUsize::new(80)
+            ),
+            "foo\n\nbaz"
+        );
+    }
+
+    #[test]
+    fn markdown() {
+        let url = Url::parse("https://example.com/").unwrap();
+        assert_eq!(
+            render_markdown("Some *text* with [a link](/post/1).\n\n- one\n- two", &url),
+            render(
+                "<p>Some <em>text</em> with <a href=\"/post/1\">a link</a>.</p>\
+                 <ul><li>one</li><li>two</li></ul>",
+                &url
+            )
+        );
+        assert_eq!(render_markdown("<b>not bold</b>", &url), "<b>not bold</b>");
+    }
 }
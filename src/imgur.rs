@@ -6,6 +6,7 @@ use url::Url;
 
 use crate::Collection;
 use crate::Content;
+use crate::Gallery;
 use crate::Item;
 use crate::process_generic;
 
@@ -79,13 +80,26 @@ pub(crate) fn process(agent: &Agent, url: &mut Url) -> Option<anyhow::Result<Con
                         &Url::parse(&album.images[0].link)
                             .context("Imgur API returned invalid URL")?,
                     )
+                } else if album
+                    .images
+                    .iter()
+                    .all(|i| i.media_type.starts_with("image/"))
+                {
+                    let urls = album
+                        .images
+                        .iter()
+                        .map(|i| Url::parse(&i.link))
+                        .collect::<Result<Vec<_>, _>>()
+                        .context("Imgur API returned invalid URL")?;
+                    Ok(Content::Gallery(Gallery::download(
+                        agent,
+                        &urls,
+                        album.caption(),
+                    )?))
                 } else {
+                    // Videos can't be shown by an image viewer.
                     Ok(Content::Collection(Collection {
-                        title: if album.title.is_empty() {
-                            None
-                        } else {
-                            Some(album.title)
-                        },
+                        title: Some(album.title).filter(|t| !t.is_empty()),
                         description: album.description,
                         items: album
                             .images
@@ -133,11 +147,38 @@ struct Album {
     images: Vec<AlbumImage>,
 }
 
+impl Album {
+    /// Return the caption of the album as a gallery: its title and description followed by
+    /// those of each image which has any.
+    fn caption(&self) -> Option<String> {
+        let mut parts: Vec<String> = [Some(&self.title), self.description.as_ref()]
+            .into_iter()
+            .flatten()
+            .filter(|p| !p.is_empty())
+            .cloned()
+            .collect();
+        for (i, image) in self.images.iter().enumerate() {
+            let caption = [image.title.as_deref(), image.description.as_deref()]
+                .into_iter()
+                .flatten()
+                .filter(|c| !c.is_empty())
+                .collect::<Vec<_>>()
+                .join(": ");
+            if !caption.is_empty() {
+                parts.push(format!("{}. {caption}", i + 1));
+            }
+        }
+        Some(parts.join("\n\n")).filter(|c| !c.is_empty())
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct AlbumImage {
     title: Option<String>,
     description: Option<String>,
     link: String,
+    #[serde(rename = "type")]
+    media_type: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -147,6 +188,8 @@ struct Image {
 
 #[cfg(test)]
 mod tests {
+    use super::Album;
+    use super::AlbumImage;
     use super::Path;
     use crate::tests::parse_path_tests;
 
@@ -169,4 +212,31 @@ mod tests {
         (image_title, "/title-abcdefg", Some(Path::Image("abcdefg"))),
         (unknown, "/unknown/path", None),
     );
+
+    #[test]
+    fn caption() {
+        let image = |title: Option<&str>, description: Option<&str>| AlbumImage {
+            title: title.map(ToOwned::to_owned),
+            description: description.map(ToOwned::to_owned),
+            link: "https://i.imgur.com/abcdefg.png".to_owned(),
+            media_type: "image/png".to_owned(),
+        };
+        let mut album = Album {
+            title: "Title".to_owned(),
+            description: None,
+            images: vec![
+                image(Some("First"), Some("About the first")),
+                image(None, None),
+                image(None, Some("About the third")),
+            ],
+        };
+        assert_eq!(
+            album.caption().as_deref(),
+            Some("Title\n\n1. First: About the first\n\n3. About the third")
+        );
+
+        album.title = String::new();
+        album.images = vec![image(None, Some(""))];
+        assert_eq!(album.caption(), None);
+    }
 }
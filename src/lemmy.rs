@@ -0,0 +1,380 @@
+use scraper::Html;
+use scraper::Selector;
+use serde::Deserialize;
+use ureq::Agent;
+use url::Url;
+
+use crate::Collection;
+use crate::Content;
+use crate::Item;
+use crate::Post;
+use crate::PostThread;
+use crate::Reply;
+use crate::TextType;
+use crate::Timestamp;
+use crate::author_name;
+use crate::html;
+use crate::nest_replies;
+
+#[derive(Debug, PartialEq)]
+enum Path<'a> {
+    Comment { comment_id: &'a str },
+    Community { community: &'a str },
+    Post { post_id: &'a str },
+}
+
+fn parse_path(url: &Url) -> Option<Path<'_>> {
+    let path_segments: Vec<_> = url
+        .path_segments()
+        .unwrap_or_else(|| "".split('/'))
+        .collect();
+
+    Some(match path_segments.as_slice() {
+        ["c", community] => Path::Community { community },
+        ["comment", comment_id] | ["post", _, comment_id] => Path::Comment { comment_id },
+        ["post", post_id] => Path::Post { post_id },
+        _ => return None,
+    })
+}
+
+/// Return whether `url` is a comment, community or post.
+pub(crate) fn supports_path(url: &Url) -> bool {
+    parse_path(url).is_some()
+}
+
+pub(crate) fn try_process(
+    agent: &Agent,
+    url: &Url,
+    tree: &Html,
+) -> Option<anyhow::Result<Content>> {
+    // lemmy-ui passes its initial state to the client in a script.
+    if !tree
+        .select(&Selector::parse("script").expect("valid selector"))
+        .any(|e| e.inner_html().contains("window.isoData"))
+    {
+        return None;
+    }
+
+    process(agent, url)
+}
+
+pub(crate) fn process(agent: &Agent, url: &Url) -> Option<anyhow::Result<Content>> {
+    process_api(agent, url, "/api/v3/")
+}
+
+/// `PieFed` implements the Lemmy API under a different path.
+pub(crate) fn process_piefed(agent: &Agent, url: &Url) -> Option<anyhow::Result<Content>> {
+    process_api(agent, url, "/api/alpha/")
+}
+
+fn process_api(agent: &Agent, url: &Url, api_path: &str) -> Option<anyhow::Result<Content>> {
+    let path = parse_path(url)?;
+    let api_base = url.join(api_path).expect("URL is valid");
+
+    Some((|| match path {
+        Path::Comment { comment_id } => {
+            let comment: CommentResponse = agent
+                .get(api_base.join("comment")?.as_str())
+                .query("id", comment_id)
+                .call()?
+                .body_mut()
+                .read_json()?;
+            let post = get_post(
+                agent,
+                &api_base,
+                &comment.comment_view.comment.post_id.to_string(),
+            )?;
+            // The comment itself is included among those under it.
+            let main_id = comment.comment_view.comment.id;
+            let comments = get_comments(agent, &api_base, "parent_id", comment_id)?;
+
+            Ok(Content::Text(TextType::PostThread(PostThread {
+                title: Some(post.post.name.clone()),
+                before: vec![post.render(url)],
+                main: comment.comment_view.render(url),
+                after: render_comments(
+                    comments
+                        .into_iter()
+                        .filter(|c| c.comment.id != main_id)
+                        .collect(),
+                    url,
+                ),
+            })))
+        }
+
+        Path::Community { community } => {
+            let community_info: CommunityResponse = agent
+                .get(api_base.join("community")?.as_str())
+                .query("name", community)
+                .call()?
+                .body_mut()
+                .read_json()?;
+            let posts: PostListResponse = agent
+                .get(api_base.join("post/list")?.as_str())
+                .query("community_name", community)
+                .query("sort", "New")
+                .query("limit", "20")
+                .call()?
+                .body_mut()
+                .read_json()?;
+
+            let community = community_info.community_view.community;
+            Ok(Content::Collection(Collection {
+                title: Some(community.title),
+                description: community
+                    .description
+                    .map(|d| html::render_markdown(&d, url)),
+                items: posts
+                    .posts
+                    .into_iter()
+                    .map(|p| Item {
+                        title: Some(p.post.name.clone()),
+                        url: url
+                            .join(&format!("/post/{}", p.post.id))
+                            .map_or_else(|_| p.post.ap_id.clone(), Into::into),
+                        description: Some(p.creator.name()),
+                    })
+                    .collect(),
+            }))
+        }
+
+        Path::Post { post_id } => {
+            let post = get_post(agent, &api_base, post_id)?;
+            let comments = get_comments(agent, &api_base, "post_id", post_id)?;
+
+            Ok(Content::Text(TextType::PostThread(PostThread {
+                title: Some(post.post.name.clone()),
+                before: vec![],
+                main: post.render(url),
+                after: render_comments(comments, url),
+            })))
+        }
+    })())
+}
+
+fn get_post(agent: &Agent, api_base: &Url, post_id: &str) -> anyhow::Result<PostView> {
+    let response: PostResponse = agent
+        .get(api_base.join("post")?.as_str())
+        .query("id", post_id)
+        .call()?
+        .body_mut()
+        .read_json()?;
+    Ok(response.post_view)
+}
+
+/// Return the comments under the post or comment with ID `id`. `key` selects which.
+fn get_comments(
+    agent: &Agent,
+    api_base: &Url,
+    key: &str,
+    id: &str,
+) -> anyhow::Result<Vec<CommentView>> {
+    let response: CommentListResponse = agent
+        .get(api_base.join("comment/list")?.as_str())
+        .query(key, id)
+        // Lemmy returns every comment to max_depth ignoring limit.
+        .query("max_depth", "8")
+        .query("sort", "Hot")
+        .query("type_", "All")
+        .call()?
+        .body_mut()
+        .read_json()?;
+    Ok(response.comments)
+}
+
+fn render_comments(comments: Vec<CommentView>, url: &Url) -> Vec<Reply> {
+    nest_replies(comments, |c| c.comment.id, |c| parent_id(&c.comment.path))
+        .into_iter()
+        .map(|(depth, c)| Reply {
+            depth,
+            post: c.render(url),
+        })
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+struct Comment {
+    content: String,
+    #[serde(default)]
+    deleted: bool,
+    id: u64,
+    /// The IDs of the comment's ancestors and itself separated by dots starting with 0.
+    path: String,
+    post_id: u64,
+    published: Timestamp,
+    #[serde(default)]
+    removed: bool,
+    updated: Option<Timestamp>,
+}
+
+/// Return the ID of the parent of a comment from its `path` or `None` for top-level comments.
+fn parent_id(path: &str) -> Option<u64> {
+    path.rsplit('.').nth(1)?.parse().ok().filter(|&i| i != 0)
+}
+
+#[derive(Debug, Deserialize)]
+struct CommentListResponse {
+    comments: Vec<CommentView>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommentResponse {
+    comment_view: CommentView,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommentView {
+    comment: Comment,
+    counts: Counts,
+    creator: Person,
+}
+
+impl CommentView {
+    fn render(self, url: &Url) -> Post {
+        let mut labels = vec![];
+        if self.comment.deleted {
+            labels.push("deleted".to_owned());
+        }
+        if self.comment.removed {
+            labels.push("removed".to_owned());
+        }
+
+        Post {
+            author: self.creator.name(),
+            body: html::render_markdown(&self.comment.content, url),
+            urls: vec![],
+            timestamp: Some(self.comment.published),
+            score: Some(self.counts.score),
+            edited: self.comment.updated.is_some(),
+            labels,
+            quote: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Community {
+    description: Option<String>,
+    title: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommunityResponse {
+    community_view: CommunityView,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommunityView {
+    community: Community,
+}
+
+#[derive(Debug, Deserialize)]
+struct Counts {
+    score: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Person {
+    actor_id: String,
+    display_name: Option<String>,
+    name: String,
+}
+
+impl Person {
+    fn name(&self) -> String {
+        let handle = match Url::parse(&self.actor_id)
+            .ok()
+            .as_ref()
+            .and_then(Url::host_str)
+        {
+            Some(host) => format!("{}@{host}", self.name),
+            None => self.name.clone(),
+        };
+        author_name(self.display_name.as_deref().unwrap_or_default(), &handle)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct LemmyPost {
+    ap_id: String,
+    body: Option<String>,
+    id: u64,
+    name: String,
+    published: Timestamp,
+    updated: Option<Timestamp>,
+    url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PostListResponse {
+    posts: Vec<PostView>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PostResponse {
+    post_view: PostView,
+}
+
+#[derive(Debug, Deserialize)]
+struct PostView {
+    counts: Counts,
+    creator: Person,
+    post: LemmyPost,
+}
+
+impl PostView {
+    fn render(self, url: &Url) -> Post {
+        Post {
+            author: self.creator.name(),
+            body: html::render_markdown(self.post.body.as_deref().unwrap_or_default(), url),
+            urls: self.post.url.into_iter().collect(),
+            timestamp: Some(self.post.published),
+            score: Some(self.counts.score),
+            edited: self.post.updated.is_some(),
+            labels: vec![],
+            quote: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Path;
+    use super::parent_id;
+    use crate::tests::parse_path_tests;
+
+    parse_path_tests!(
+        super::parse_path,
+        "https://example.com{}",
+        (
+            comment,
+            "/comment/12345",
+            Some(Path::Comment {
+                comment_id: "12345"
+            })
+        ),
+        (
+            post_comment,
+            "/post/678/12345",
+            Some(Path::Comment {
+                comment_id: "12345"
+            })
+        ),
+        (
+            community,
+            "/c/rust@example.org",
+            Some(Path::Community {
+                community: "rust@example.org"
+            })
+        ),
+        (post, "/post/678", Some(Path::Post { post_id: "678" })),
+        (unknown, "/u/example", None),
+    );
+
+    #[test]
+    fn parent() {
+        assert_eq!(parent_id("0.3"), None);
+        assert_eq!(parent_id("0.2.3"), Some(2));
+        assert_eq!(parent_id("0.1.2.3"), Some(2));
+    }
+}
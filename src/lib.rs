@@ -11,18 +11,20 @@
 
 use std::collections::HashMap;
 use std::env;
-use std::ffi::OsStr;
 use std::ffi::OsString;
 use std::fmt;
 use std::fmt::Display;
 use std::fmt::Formatter;
+use std::hash::Hash;
 use std::io;
 use std::io::Read;
 use std::io::Write;
 use std::iter;
 use std::iter::Iterator;
 use std::num::NonZeroUsize;
+use std::panic;
 use std::process::Command;
+use std::thread;
 
 use anyhow::anyhow;
 use anyhow::bail;
@@ -37,7 +39,11 @@ use url::Url;
 mod config;
 pub use config::Config;
 
+mod timestamp;
+use timestamp::Timestamp;
+
 mod bsky;
+mod cache;
 mod cgit;
 mod discourse;
 mod gitea;
@@ -45,9 +51,17 @@ mod github;
 mod gitweb;
 mod html;
 mod imgur;
+mod lemmy;
+mod links;
 mod lobsters;
 mod mastodon;
+mod mbin;
+mod menu;
+mod misskey;
 mod nextcloud;
+mod nodeinfo;
+mod peertube;
+mod reddit;
 mod stackoverflow;
 mod wikimedia;
 
@@ -56,6 +70,7 @@ const LINE_LENGTH: usize = 80;
 enum Content {
     Audio(Url),
     Collection(Collection),
+    Gallery(Gallery),
     Image(BodyReader<'static>),
     Pdf(BodyReader<'static>, Option<NonZeroUsize>),
     Text(TextType),
@@ -74,6 +89,63 @@ struct Item {
     description: Option<String>,
 }
 
+/// Several images shown together along with a caption for them all.
+struct Gallery {
+    caption: Option<String>,
+    files: Vec<NamedTempFile>,
+}
+
+impl Gallery {
+    /// Download the images at `urls` into temporary files.
+    fn download(agent: &Agent, urls: &[Url], caption: Option<String>) -> anyhow::Result<Self> {
+        // Enough to overlap the latency of requests without flooding a server.
+        const MAX_CONCURRENT: usize = 8;
+        const MAX_IMAGE_LEN: u64 = 64 * 1024 * 1024;
+
+        let mut files = Vec::with_capacity(urls.len());
+        for chunk in urls.chunks(MAX_CONCURRENT) {
+            thread::scope(|scope| {
+                let downloads: Vec<_> = chunk
+                    .iter()
+                    .map(|url| {
+                        scope.spawn(move || -> anyhow::Result<NamedTempFile> {
+                            let mut response = agent.get(url.as_str()).call()?;
+                            if !response
+                                .headers()
+                                .get("Content-Type")
+                                .and_then(|v| v.to_str().ok())
+                                .is_some_and(|v| v.starts_with("image/"))
+                            {
+                                bail!("{url} is not an image");
+                            }
+                            let mut file = NamedTempFile::new()?;
+                            io::copy(
+                                &mut response
+                                    .body_mut()
+                                    .with_config()
+                                    .limit(MAX_IMAGE_LEN)
+                                    .reader(),
+                                &mut file,
+                            )?;
+                            Ok(file)
+                        })
+                    })
+                    .collect();
+                for download in downloads {
+                    files.push(
+                        download
+                            .join()
+                            .unwrap_or_else(|e| panic::resume_unwind(e))?,
+                    );
+                }
+                anyhow::Ok(())
+            })?;
+        }
+
+        Ok(Self { caption, files })
+    }
+}
+
 impl Collection {
     fn write(&self, writer: &mut impl Write) -> io::Result<()> {
         if let Some(title) = &self.title {
@@ -116,32 +188,184 @@ struct Article {
     body: String,
 }
 
+#[derive(Default)]
 struct Post {
     author: String,
     body: String,
     urls: Vec<String>,
+    timestamp: Option<Timestamp>,
+    score: Option<i64>,
+    edited: bool,
+    /// Short notes shown alongside the other details such as "accepted".
+    labels: Vec<String>,
+    /// A post this one quotes, shown after its body.
+    quote: Option<Quote>,
 }
 
-impl Display for Post {
-    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
-        write!(
-            f,
-            "{}",
-            textwrap::fill(&format!("<{}> {}", self.author, self.body), LINE_LENGTH)
-        )?;
+impl Post {
+    fn header(&self) -> String {
+        let mut details = vec![];
+        if let Some(timestamp) = self.timestamp {
+            details.push(timestamp.to_string());
+        }
+        if let Some(score) = self.score {
+            details.push(format!("{score:+}"));
+        }
+        if self.edited {
+            details.push("edited".to_owned());
+        }
+        details.extend(self.labels.iter().cloned());
+
+        if details.is_empty() {
+            format!("<{}>", self.author)
+        } else {
+            format!("<{}> [{}]", self.author, details.join(", "))
+        }
+    }
+
+    fn write_indented(&self, f: &mut Formatter, indent: &str) -> Result<(), fmt::Error> {
+        let text = textwrap::fill(
+            &format!("{} {}", self.header(), self.body),
+            Options::new(LINE_LENGTH)
+                .initial_indent(indent)
+                .subsequent_indent(indent),
+        );
+        text.split('\n').enumerate().try_for_each(|(i, line)| {
+            // Don't leave the indent as trailing whitespace on blank lines.
+            let line = if line == indent {
+                indent.trim_end()
+            } else {
+                line
+            };
+            write!(f, "{}{line}", if i == 0 { "" } else { "\n" })
+        })?;
+        if let Some(quote) = &self.quote {
+            write!(f, "\n{}\n", indent.trim_end())?;
+            quote.write_indented(f, &format!("{indent}> "))?;
+        }
         if !self.urls.is_empty() {
-            writeln!(f)?;
-            self.urls.iter().try_for_each(|u| write!(f, "\n{u}"))?;
+            write!(f, "\n{}", indent.trim_end())?;
+            self.urls
+                .iter()
+                .try_for_each(|u| write!(f, "\n{indent}{u}"))?;
         }
         Ok(())
     }
 }
 
+impl Display for Post {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        self.write_indented(f, "")
+    }
+}
+
+/// A post quoted by another, shown as a `> `-prefixed block.
+enum Quote {
+    Post(Box<Post>),
+    /// Why the quoted post can't be shown such as that it was deleted.
+    Unavailable(&'static str),
+}
+
+impl Quote {
+    fn write_indented(&self, f: &mut Formatter, indent: &str) -> Result<(), fmt::Error> {
+        match self {
+            Self::Post(post) => post.write_indented(f, indent),
+            Self::Unavailable(reason) => write!(f, "{indent}[{reason}]"),
+        }
+    }
+}
+
+/// Name an author by display name and handle, or just handle if the display name is blank.
+fn author_name(display_name: &str, handle: &str) -> String {
+    let display_name = display_name.trim();
+    if display_name.is_empty() {
+        format!("@{handle}")
+    } else {
+        format!("{display_name} (@{handle})")
+    }
+}
+
+/// Prefix `url` with `label` the way a `Collection` shows item titles.
+fn labeled_url(label: &str, url: String) -> String {
+    // Alt text may span lines, but each URL gets just one.
+    let label = label.split_whitespace().collect::<Vec<_>>().join(" ");
+    if label.is_empty() {
+        url
+    } else {
+        format!("{label}: {url}")
+    }
+}
+
 struct PostThread {
     title: Option<String>,
     main: Post,
     before: Vec<Post>,
-    after: Vec<Post>,
+    after: Vec<Reply>,
+}
+
+/// A post following the main post of a thread. `depth` is how far the post is nested, so direct
+/// replies and unthreaded comments have a depth of zero.
+struct Reply {
+    depth: usize,
+    post: Post,
+}
+
+impl From<Post> for Reply {
+    fn from(post: Post) -> Self {
+        Self { depth: 0, post }
+    }
+}
+
+impl Display for Reply {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        self.post.write_indented(f, &"  ".repeat(self.depth))
+    }
+}
+
+/// Order `posts` so each post directly follows the post it replies to and compute the depth of
+/// each. Posts whose parent is not in `posts` are treated as replies to the main post. The
+/// relative order of siblings is preserved.
+fn nest_replies<T, K: Eq + Hash>(
+    posts: Vec<T>,
+    id: impl Fn(&T) -> K,
+    parent: impl Fn(&T) -> Option<K>,
+) -> Vec<(usize, T)> {
+    let indexes: HashMap<K, usize> = posts
+        .iter()
+        .enumerate()
+        .map(|(i, post)| (id(post), i))
+        .collect();
+    let mut children: HashMap<Option<usize>, Vec<usize>> = HashMap::new();
+    for (i, post) in posts.iter().enumerate() {
+        let parent_index = parent(post).and_then(|p| indexes.get(&p).copied());
+        children.entry(parent_index).or_default().push(i);
+    }
+
+    let mut posts: Vec<Option<T>> = posts.into_iter().map(Some).collect();
+    let mut result = Vec::with_capacity(posts.len());
+    let mut stack: Vec<(usize, usize)> = children
+        .remove(&None)
+        .unwrap_or_default()
+        .into_iter()
+        .rev()
+        .map(|i| (0, i))
+        .collect();
+    while let Some((depth, i)) = stack.pop() {
+        if let Some(post) = posts[i].take() {
+            result.push((depth, post));
+            stack.extend(
+                children
+                    .remove(&Some(i))
+                    .unwrap_or_default()
+                    .into_iter()
+                    .rev()
+                    .map(|c| (depth + 1, c)),
+            );
+        }
+    }
+    // Only reachable if the replies form a cycle.
+    result.extend(posts.into_iter().flatten().map(|post| (0, post)));
+    result
 }
 
 impl TextType {
@@ -159,7 +383,8 @@ impl TextType {
                     .before
                     .iter()
                     .chain(iter::once(&thread.main))
-                    .chain(&thread.after);
+                    .map(|p| p as &dyn Display)
+                    .chain(thread.after.iter().map(|r| r as &dyn Display));
                 post_chain.enumerate().try_for_each(|(i, p)| {
                     write!(writer, "{}{p}", if i == 0 { "" } else { "\n\n" })
                 })
@@ -169,8 +394,18 @@ impl TextType {
     }
 }
 
+/// Stop reading and writing cached data under `$XDG_CACHE_HOME/zxcv`.
+///
+/// Call this before restricting filesystem access, such as with `pledge`, as anything cached is
+/// instead fetched again each time.
+pub fn disable_cache() {
+    cache::disable();
+}
+
 /// Open a program to show the content of a URL.
 ///
+/// Besides URLs, `url` may be a Bluesky AT URI, DID, or handle prefixed with `@`.
+///
 /// # Errors
 ///
 /// This function may error for a variety of reasons including but not limited to
@@ -183,7 +418,10 @@ impl TextType {
 /// The particular `Error` that `anyhow` wraps is not part of API stability promises and may change
 /// without a major version bump.
 pub fn show_url(config: &Config, url: &str) -> anyhow::Result<()> {
-    let mut url = Url::parse(url)?;
+    let mut url = match bsky::identifier_url(url) {
+        Some(url) => url?,
+        None => Url::parse(url)?,
+    };
     if url.cannot_be_a_base() {
         bail!("Non-absolute URL");
     }
@@ -191,10 +429,38 @@ pub fn show_url(config: &Config, url: &str) -> anyhow::Result<()> {
         bail!("Unsupported URL scheme");
     }
 
-    show_content(config, get_content(&mut url)?)
+    let content = get_content(config, &mut url)?;
+    if config.is_interactive()
+        && let Content::Collection(collection) = &content
+    {
+        return browse(config, &collection.items);
+    }
+
+    if config.is_links() {
+        let links = links::collect(&content);
+        show_content(config, content)?;
+        return browse(config, &links);
+    }
+
+    show_content(config, content)
 }
 
-fn get_content(url: &mut Url) -> anyhow::Result<Content> {
+/// Show the items the user chooses from `items` until they choose none.
+fn browse(config: &Config, items: &[Item]) -> anyhow::Result<()> {
+    if items.is_empty() {
+        return Ok(());
+    }
+    while let Some(i) = menu::choose(config, items)? {
+        // A failing item shouldn't end the session as others may still work.
+        let item_url = &items[i].url;
+        if let Err(e) = show_url(config, item_url) {
+            eprintln!("{item_url}: {e:#}");
+        }
+    }
+    Ok(())
+}
+
+fn get_content(config: &Config, url: &mut Url) -> anyhow::Result<Content> {
     let agent = Agent::config_builder()
         .user_agent(format!("zxcv/{}", env!("CARGO_PKG_VERSION")))
         .build()
@@ -204,7 +470,7 @@ fn get_content(url: &mut Url) -> anyhow::Result<Content> {
         return process_generic(&agent, url);
     }
 
-    if let Some(content) = process_specific(&agent, url) {
+    if let Some(content) = process_specific(&agent, config, url) {
         return content;
     }
 
@@ -285,12 +551,16 @@ fn rewrite_url(url: &mut Url) -> bool {
     true
 }
 
-fn process_specific(agent: &Agent, url: &mut Url) -> Option<anyhow::Result<Content>> {
+fn process_specific(
+    agent: &Agent,
+    config: &Config,
+    url: &mut Url,
+) -> Option<anyhow::Result<Content>> {
     let hostname = url.host_str()?;
 
     #[expect(clippy::match_same_arms)]
     match hostname {
-        "bsky.app" => bsky::process(agent, url),
+        "bsky.app" | "deer.social" | "main.bsky.dev" => bsky::process(agent, config, url),
 
         "codeberg.org" => gitea::process(agent, url),
 
@@ -322,15 +592,19 @@ fn process_specific(agent: &Agent, url: &mut Url) -> Option<anyhow::Result<Conte
             Some(github::gist::process_by_id(agent, &gist_pair.1))
         }
 
+        "reddit.com" | "old.reddit.com" | "www.reddit.com" | "redd.it" => {
+            reddit::process(agent, url)
+        }
+
         "soundcloud.com" | "m.soundcloud.com" => Some(Ok(Content::Audio(url.clone()))),
 
         "tenor.com" => Some(image_via_selector(agent, url, ".main-container .Gif > img")),
 
         "twitch.tv" | "www.twitch.tv" => Some(Ok(Content::Video(url.clone()))),
 
-        "vimeo.com" => Some(Ok(Content::Video(url.clone()))),
+        "v.redd.it" => Some(Ok(Content::Video(url.clone()))),
 
-        "en.wikipedia.org" => wikimedia::process(agent, url),
+        "vimeo.com" => Some(Ok(Content::Video(url.clone()))),
 
         "xkcd.com" | "m.xkcd.com" => Some(image_via_selector(agent, url, "#comic img")),
 
@@ -339,11 +613,52 @@ fn process_specific(agent: &Agent, url: &mut Url) -> Option<anyhow::Result<Conte
         }
 
         _ => {
-            if let Some(result) = stackoverflow::process(agent, url) {
+            if config.is_bsky_frontend(hostname) {
+                return bsky::process(agent, config, url);
+            }
+
+            if let Some(result) = stackoverflow::process(agent, config, url) {
                 return Some(result);
             }
 
-            None
+            if wikimedia::is_wikimedia_host(hostname) {
+                return wikimedia::process(agent, url);
+            }
+
+            // Looking up a server's software costs a request to a host that may not even run one
+            // of these, so only do it when some module could show the URL's path.
+            if ![
+                discourse::supports_path,
+                gitea::supports_path,
+                lemmy::supports_path,
+                mastodon::supports_path,
+                mbin::supports_path,
+                misskey::supports_path,
+                peertube::supports_path,
+            ]
+            .iter()
+            .any(|supports_path| supports_path(url))
+            {
+                return None;
+            }
+
+            match nodeinfo::software(agent, url)?.as_str() {
+                "discourse" => discourse::process(agent, url),
+                "forgejo" | "gitea" => gitea::process(agent, url),
+                "lemmy" => lemmy::process(agent, url),
+                "mbin" => mbin::process(agent, url),
+                "piefed" => lemmy::process_piefed(agent, url),
+                "peertube" => peertube::process(agent, url),
+                "pixelfed" => mastodon::process_pixelfed(agent, url),
+                // Servers implementing the Mastodon API
+                "akkoma" | "gotosocial" | "hometown" | "iceshrimp" | "mastodon" | "pleroma" => {
+                    mastodon::process(agent, url)
+                }
+                "cherrypick" | "firefish" | "foundkey" | "misskey" | "sharkey" => {
+                    misskey::process(agent, url)
+                }
+                _ => None,
+            }
         }
     }
 }
@@ -393,8 +708,12 @@ fn process_html(agent: &Agent, url: &Url, tree: &Html) -> anyhow::Result<Content
         discourse::try_process,
         gitea::try_process,
         gitweb::try_process,
+        lemmy::try_process,
         mastodon::try_process,
+        misskey::try_process,
         nextcloud::try_process,
+        peertube::try_process,
+        wikimedia::try_process,
         process_main_text,
         process_body,
     ] {
@@ -454,79 +773,106 @@ fn image_via_selector(agent: &Agent, url: &Url, selector: &str) -> anyhow::Resul
     process_generic(agent, &url)
 }
 
-fn show_content(config: &Config, mut content: Content) -> anyhow::Result<()> {
+/// The arguments each `%` and character in a command's argv is replaced with.
+type Replacements = HashMap<char, Vec<OsString>>;
+
+fn show_content(config: &Config, content: Content) -> anyhow::Result<()> {
     let argv = config.get_argv(&content);
     let pager = env::var("PAGER")
         .unwrap_or_else(|_| "less".to_owned())
         .into();
 
-    // replacements are documented with Config.
-    let (file, mut replacements): (Option<NamedTempFile>, HashMap<char, Option<OsString>>) =
-        match content {
-            Content::Audio(url) | Content::Video(url) => {
-                (None, [('u', Some(url.as_str().into()))].into())
-            }
+    // The files must live until the command exits.
+    let (_files, replacements) = content_replacements(content, pager)?;
+    let exit_status = Command::new(&argv[0])
+        .args(expand_args(&argv[1..], &replacements)?)
+        .status()?;
+    if exit_status.success() {
+        Ok(())
+    } else {
+        bail!("Command exited {exit_status}");
+    }
+}
 
-            Content::Collection(collection) => {
-                let mut file = NamedTempFile::new()?;
-                collection.write(&mut file)?;
-                (Some(file), [('p', Some(pager))].into())
-            }
+/// Write `content` to any files needed to show it and return those with the arguments for each
+/// replacement, as documented with Config. Each expands to any number of arguments.
+fn content_replacements(
+    mut content: Content,
+    pager: OsString,
+) -> anyhow::Result<(Vec<NamedTempFile>, Replacements)> {
+    let (files, mut replacements): (Vec<NamedTempFile>, Replacements) = match content {
+        Content::Audio(url) | Content::Video(url) => {
+            (vec![], [('u', vec![url.as_str().into()])].into())
+        }
 
-            Content::Image(ref mut reader) => {
-                let mut file = NamedTempFile::new()?;
-                io::copy(reader, &mut file)?;
-                (Some(file), [].into())
-            }
+        Content::Collection(collection) => {
+            let mut file = NamedTempFile::new()?;
+            collection.write(&mut file)?;
+            (vec![file], [('p', vec![pager])].into())
+        }
 
-            Content::Pdf(ref mut reader, page) => {
-                let mut file = NamedTempFile::new()?;
-                io::copy(reader, &mut file)?;
-                (
-                    Some(file),
-                    [('p', page.map(|p| p.to_string().into()))].into(),
-                )
-            }
+        Content::Gallery(gallery) => {
+            let replacements = [
+                ('c', gallery.caption.into_iter().map(Into::into).collect()),
+                ('F', gallery.files.iter().map(|f| f.path().into()).collect()),
+            ]
+            .into();
+            (gallery.files, replacements)
+        }
 
-            Content::Text(text) => {
-                let mut file = NamedTempFile::new()?;
-                text.write(&mut file)?;
-                (Some(file), [('p', Some(pager))].into())
-            }
-        };
+        Content::Image(ref mut reader) => {
+            let mut file = NamedTempFile::new()?;
+            io::copy(reader, &mut file)?;
+            (vec![file], [].into())
+        }
 
-    if let Some(file) = &file {
-        replacements.insert('f', Some(file.path().into()));
-    }
+        Content::Pdf(ref mut reader, page) => {
+            let mut file = NamedTempFile::new()?;
+            io::copy(reader, &mut file)?;
+            (
+                vec![file],
+                [(
+                    'p',
+                    page.map(|p| p.to_string().into()).into_iter().collect(),
+                )]
+                .into(),
+            )
+        }
 
-    let mut command = Command::new(&argv[0]);
-    command.args(
-        argv[1..]
-            .iter()
-            .filter_map(|arg| {
-                if arg.chars().count() == 2 && arg.starts_with('%') {
-                    let char = arg.chars().nth(1).expect("length checked above");
-                    Some(
-                        replacements
-                            .get(&char)
-                            .map(Option::as_deref)
-                            .ok_or_else(|| anyhow!("%{char} is not valid for this content type"))
-                            .transpose()?
-                            .map(AsRef::as_ref),
-                    )
-                } else {
-                    Some(Ok(arg.as_ref()))
-                }
-            })
-            .collect::<anyhow::Result<Vec<&OsStr>>>()?,
-    );
+        Content::Text(text) => {
+            let mut file = NamedTempFile::new()?;
+            text.write(&mut file)?;
+            (vec![file], [('p', vec![pager])].into())
+        }
+    };
 
-    let exit_status = command.status()?;
-    if exit_status.success() {
-        Ok(())
-    } else {
-        bail!("Command exited {exit_status}");
+    if let [file] = files.as_slice()
+        && !replacements.contains_key(&'F')
+    {
+        replacements.insert('f', vec![file.path().into()]);
+    }
+    Ok((files, replacements))
+}
+
+/// Replace each argument of `args` which is `%` and a character with the arguments for that
+/// character in `replacements`.
+fn expand_args(args: &[String], replacements: &Replacements) -> anyhow::Result<Vec<OsString>> {
+    let mut expanded = Vec::with_capacity(args.len());
+    for arg in args {
+        if arg.chars().count() == 2 && arg.starts_with('%') {
+            let char = arg.chars().nth(1).expect("length checked above");
+            expanded.extend(
+                replacements
+                    .get(&char)
+                    .ok_or_else(|| anyhow!("%{char} is not valid for this content type"))?
+                    .iter()
+                    .cloned(),
+            );
+        } else {
+            expanded.push(arg.into());
+        }
     }
+    Ok(expanded)
 }
 
 fn read_raw_response(response: ureq::http::Response<ureq::Body>) -> io::Result<Vec<u8>> {
@@ -548,8 +894,28 @@ fn read_raw_response(response: ureq::http::Response<ureq::Body>) -> io::Result<V
 
 #[cfg(test)]
 mod tests {
+    use std::ffi::OsString;
+    use std::io::BufRead;
+    use std::io::BufReader;
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::thread;
+
+    use tempfile::NamedTempFile;
+    use ureq::Agent;
     use url::Url;
 
+    use super::Content;
+    use super::Gallery;
+    use super::Post;
+    use super::PostThread;
+    use super::Quote;
+    use super::Replacements;
+    use super::Reply;
+    use super::TextType;
+    use super::content_replacements;
+    use super::expand_args;
+    use super::nest_replies;
     use super::rewrite_url;
 
     macro_rules! rewrite_tests {
@@ -649,14 +1015,294 @@ mod tests {
         assert_eq!(url, expected);
     }
 
+    /// Serve `count` requests on a local port, answering each path ending in `.png` with an image
+    /// and anything else with HTML, and return the URL of the server.
+    fn serve(count: usize) -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("can bind to a local port");
+        let url = Url::parse(&format!(
+            "http://{}/",
+            listener.local_addr().expect("listener has an address")
+        ))
+        .expect("URL is valid");
+        thread::spawn(move || {
+            for stream in listener.incoming().take(count) {
+                let mut stream = stream.expect("connection succeeds");
+                let mut request_line = String::new();
+                let mut reader = BufReader::new(&stream);
+                reader
+                    .read_line(&mut request_line)
+                    .expect("request is readable");
+                // Skip the headers.
+                let mut line = String::new();
+                while reader.read_line(&mut line).is_ok_and(|n| n > 2) {
+                    line.clear();
+                }
+                let (content_type, body) = if request_line.contains(".png ") {
+                    ("image/png", request_line.as_str())
+                } else {
+                    ("text/html", "<html></html>")
+                };
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len(),
+                )
+                .expect("response is writable");
+            }
+        });
+        url
+    }
+
+    /// Serve one request per pair in `responses` on a local port, answering each with the JSON of
+    /// the longest key its target contains, and return the URL of the server.
+    pub(crate) fn serve_json(responses: Vec<(&'static str, String)>) -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("can bind to a local port");
+        let url = Url::parse(&format!(
+            "http://{}/",
+            listener.local_addr().expect("listener has an address")
+        ))
+        .expect("URL is valid");
+        thread::spawn(move || {
+            for stream in listener.incoming().take(responses.len()) {
+                let mut stream = stream.expect("connection succeeds");
+                let mut request_line = String::new();
+                let mut reader = BufReader::new(&stream);
+                reader
+                    .read_line(&mut request_line)
+                    .expect("request is readable");
+                // Skip the headers.
+                let mut line = String::new();
+                while reader.read_line(&mut line).is_ok_and(|n| n > 2) {
+                    line.clear();
+                }
+                let target = request_line.split(' ').nth(1).unwrap_or_default();
+                let (status, body) = responses
+                    .iter()
+                    .filter(|(key, _)| target.contains(key))
+                    .max_by_key(|(key, _)| key.len())
+                    .map_or(("404 Not Found", ""), |(_, body)| ("200 OK", body));
+                write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Type: application/json\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len(),
+                )
+                .expect("response is writable");
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn gallery_download() {
+        let base = serve(2);
+        let urls = [
+            base.join("a.png").expect("URL is valid"),
+            base.join("b.png").expect("URL is valid"),
+        ];
+        let gallery = Gallery::download(
+            &Agent::new_with_defaults(),
+            &urls,
+            Some("Caption".to_owned()),
+        )
+        .expect("images download");
+        assert_eq!(gallery.caption.as_deref(), Some("Caption"));
+        // Each file holds the image at the URL of the same index.
+        let contents: Vec<_> = gallery
+            .files
+            .iter()
+            .map(|f| std::fs::read_to_string(f.path()).expect("file is readable"))
+            .collect();
+        assert!(contents[0].starts_with("GET /a.png "));
+        assert!(contents[1].starts_with("GET /b.png "));
+    }
+
+    #[test]
+    fn gallery_download_not_image() {
+        let base = serve(2);
+        let urls = [
+            base.join("a.png").expect("URL is valid"),
+            base.join("page").expect("URL is valid"),
+        ];
+        let Err(error) = Gallery::download(&Agent::new_with_defaults(), &urls, None) else {
+            panic!("a page is not an image");
+        };
+        assert_eq!(error.to_string(), format!("{} is not an image", urls[1]));
+    }
+
+    #[test]
+    fn gallery_arguments() {
+        let argv = |args: &[&str], replacements: &Replacements| {
+            expand_args(
+                &args.iter().map(|&a| a.to_owned()).collect::<Vec<_>>(),
+                replacements,
+            )
+        };
+        let gallery = |caption: Option<&str>| {
+            Content::Gallery(Gallery {
+                caption: caption.map(ToOwned::to_owned),
+                files: vec![
+                    NamedTempFile::new().expect("temporary file"),
+                    NamedTempFile::new().expect("temporary file"),
+                ],
+            })
+        };
+
+        let (files, replacements) =
+            content_replacements(gallery(Some("A caption")), "less".into()).expect("no I/O needed");
+        let paths: Vec<OsString> = files.iter().map(|f| f.path().into()).collect();
+        assert_eq!(
+            argv(&["-t", "%c", "%F"], &replacements).expect("replacements are valid"),
+            [vec!["-t".into(), "A caption".into()], paths.clone(),].concat()
+        );
+        // A single path is ambiguous for several files.
+        assert!(argv(&["%f"], &replacements).is_err());
+
+        // Without a caption %c expands to nothing.
+        let (files, replacements) =
+            content_replacements(gallery(None), "less".into()).expect("no I/O needed");
+        let paths: Vec<OsString> = files.iter().map(|f| f.path().into()).collect();
+        assert_eq!(
+            argv(&["%c", "%F"], &replacements).expect("replacements are valid"),
+            paths
+        );
+    }
+
+    #[test]
+    fn single_file_arguments() {
+        let (files, replacements) = content_replacements(
+            Content::Text(TextType::Raw(b"text".to_vec())),
+            "less".into(),
+        )
+        .expect("temporary file is writable");
+        assert_eq!(
+            expand_args(&["%p".to_owned(), "%f".to_owned()], &replacements)
+                .expect("replacements are valid"),
+            [OsString::from("less"), files[0].path().into()]
+        );
+    }
+
+    #[test]
+    fn nest_replies_orders_by_parent() {
+        let posts = vec![
+            (1, None),
+            (2, Some(1)),
+            (3, None),
+            (4, Some(2)),
+            (5, Some(1)),
+        ];
+        let nested: Vec<_> = nest_replies(posts, |p| p.0, |p| p.1)
+            .into_iter()
+            .map(|(depth, p)| (depth, p.0))
+            .collect();
+        assert_eq!(nested, [(0, 1), (1, 2), (2, 4), (1, 5), (0, 3)]);
+    }
+
+    #[test]
+    fn nest_replies_unknown_parent() {
+        let posts = vec![(2, Some(1)), (3, Some(2))];
+        let nested: Vec<_> = nest_replies(posts, |p| p.0, |p| p.1)
+            .into_iter()
+            .map(|(depth, p)| (depth, p.0))
+            .collect();
+        assert_eq!(nested, [(0, 2), (1, 3)]);
+    }
+
+    #[test]
+    fn post_thread_indents_replies() {
+        let post = |author: &str, body: &str| Post {
+            author: author.to_owned(),
+            body: body.to_owned(),
+            ..Post::default()
+        };
+        let thread = TextType::PostThread(PostThread {
+            title: None,
+            before: vec![],
+            main: post("a", "main"),
+            after: vec![
+                post("b", "reply").into(),
+                Reply {
+                    depth: 1,
+                    post: Post {
+                        urls: vec!["https://example.com/".to_owned()],
+                        ..post("c", "nested\n\nreply")
+                    },
+                },
+            ],
+        });
+        let mut output = vec![];
+        thread.write(&mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "<a> main\n\n<b> reply\n\n  <c> nested\n\n  reply\n\n  https://example.com/"
+        );
+    }
+
+    #[test]
+    fn post_thread_quotes_once_per_line() {
+        let long = "word ".repeat(20);
+        let thread = TextType::PostThread(PostThread {
+            title: None,
+            before: vec![],
+            main: Post {
+                author: "a".to_owned(),
+                body: "main".to_owned(),
+                ..Post::default()
+            },
+            after: vec![Reply {
+                depth: 1,
+                post: Post {
+                    author: "b".to_owned(),
+                    body: "reply".to_owned(),
+                    quote: Some(Quote::Post(Box::new(Post {
+                        author: "c".to_owned(),
+                        body: long.trim_end().to_owned(),
+                        ..Post::default()
+                    }))),
+                    ..Post::default()
+                },
+            }],
+        });
+        let mut output = vec![];
+        thread.write(&mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!(
+                "<a> main\n\n  <b> reply\n\n  > <c> {}\n  > {}",
+                "word ".repeat(14).trim_end(),
+                "word ".repeat(6).trim_end(),
+            )
+        );
+    }
+
+    #[test]
+    fn post_header_details() {
+        let post = Post {
+            author: "a".to_owned(),
+            body: "body".to_owned(),
+            timestamp: Some(serde_json::from_str("1299215167").unwrap()),
+            score: Some(-3),
+            edited: true,
+            ..Post::default()
+        };
+        assert_eq!(
+            post.to_string(),
+            "<a> [2011-03-04 05:06 UTC, -3, edited] body"
+        );
+    }
+
     macro_rules! parse_path_tests {
-        ($parse_path: expr, $url_format: expr, $(($name: ident, $path: expr, $expected: pat),)*) => {
+        ($parse_path: expr, $url_format: expr, $(($name: ident, $path: expr, $expected: pat $(if $guard: expr)?),)*) => {
             $(
                 #[test]
                 fn $name() {
                     assert!($path.starts_with('/'));
                     let url = url::Url::parse(&format!($url_format, $path)).unwrap();
-                    assert!(matches!($parse_path(&url), $expected));
+                    match $parse_path(&url) {
+                        $expected $(if $guard)? => {}
+                        path => panic!("Unexpected {path:?}"),
+                    }
                 }
             )*
         }
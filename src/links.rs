@@ -0,0 +1,175 @@
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+use url::Url;
+
+use crate::Content;
+use crate::Item;
+use crate::Post;
+use crate::TextType;
+
+/// Return the URLs in `content` in order of appearance without duplicates.
+pub(crate) fn collect(content: &Content) -> Vec<Item> {
+    let mut links = Links::default();
+    match content {
+        Content::Collection(collection) => {
+            if let Some(description) = &collection.description {
+                links.add_text(description);
+            }
+            for item in &collection.items {
+                links.add(item.title.clone(), &item.url);
+            }
+        }
+
+        Content::Text(TextType::Article(article)) => links.add_text(&article.body),
+        Content::Text(TextType::Post(post)) => links.add_post(post),
+        Content::Text(TextType::PostThread(thread)) => {
+            for post in &thread.before {
+                links.add_post(post);
+            }
+            links.add_post(&thread.main);
+            for reply in &thread.after {
+                links.add_post(&reply.post);
+            }
+        }
+        Content::Text(TextType::Raw(raw)) => links.add_text(&String::from_utf8_lossy(raw)),
+
+        Content::Audio(_)
+        | Content::Gallery(_)
+        | Content::Image(_)
+        | Content::Pdf(..)
+        | Content::Video(_) => {}
+    }
+    links.items
+}
+
+#[derive(Default)]
+struct Links {
+    items: Vec<Item>,
+    seen: HashSet<String>,
+}
+
+impl Links {
+    fn add(&mut self, title: Option<String>, url: &str) {
+        if self.seen.insert(url.to_owned()) {
+            self.items.push(Item {
+                title,
+                url: url.to_owned(),
+                description: None,
+            });
+        }
+    }
+
+    fn add_post(&mut self, post: &Post) {
+        self.add_text(&post.body);
+        for url in &post.urls {
+            // URLs may be labeled as by labeled_url.
+            let title = url.rsplit_once(": http").map(|(label, _)| label.to_owned());
+            for url in find_urls(url) {
+                self.add(title.clone(), &url);
+            }
+        }
+    }
+
+    fn add_text(&mut self, text: &str) {
+        for url in find_urls(text) {
+            self.add(None, &url);
+        }
+    }
+}
+
+/// Return the http and https URLs in `text` such as those of markdown links or bare URLs.
+fn find_urls(text: &str) -> impl Iterator<Item = Cow<'_, str>> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        loop {
+            let start = ["http://", "https://"]
+                .iter()
+                .filter_map(|scheme| rest.find(scheme))
+                .min()?;
+            let candidate = &rest[start..];
+
+            // A closing parenthesis ends the URL unless it closes one within the URL as in
+            // Wikipedia's URLs. This also ends URLs at the end of a markdown link.
+            let mut depth = 0;
+            let end = candidate
+                .char_indices()
+                .find(|&(_, c)| match c {
+                    '(' => {
+                        depth += 1;
+                        false
+                    }
+                    ')' if depth == 0 => true,
+                    ')' => {
+                        depth -= 1;
+                        false
+                    }
+                    _ => c.is_whitespace() || matches!(c, '<' | '>' | '"' | '`'),
+                })
+                .map_or(candidate.len(), |(i, _)| i);
+            let found = candidate[..end].trim_end_matches(['.', ',', ';', ':', '!', '?', '\'']);
+            rest = &candidate[end..];
+
+            if let Ok(url) = Url::parse(found) {
+                // Keep the text as written unless parsing changed it e.g. by escaping.
+                return Some(if url.as_str() == found {
+                    Cow::Borrowed(found)
+                } else {
+                    Cow::Owned(url.into())
+                });
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::collect;
+    use super::find_urls;
+    use crate::Content;
+    use crate::Post;
+    use crate::TextType;
+
+    #[test]
+    fn urls_in_text() {
+        let urls: Vec<_> = find_urls(
+            "See [the docs](https://example.com/docs), https://example.org/a_(b) and \
+             <https://example.net/>. Not http:// alone.",
+        )
+        .collect();
+        assert_eq!(
+            urls,
+            [
+                "https://example.com/docs",
+                "https://example.org/a_(b)",
+                "https://example.net/"
+            ]
+        );
+    }
+
+    #[test]
+    fn deduplicated_post_links() {
+        let content = Content::Text(TextType::Post(Post {
+            body: "[Example](https://example.com/) and https://example.com/".to_owned(),
+            urls: vec![
+                "A chart: https://example.com/a.png".to_owned(),
+                "https://example.com/".to_owned(),
+            ],
+            ..Post::default()
+        }));
+        let links: Vec<_> = collect(&content)
+            .into_iter()
+            .map(|i| (i.title, i.url))
+            .collect();
+        assert_eq!(
+            links,
+            [
+                (None, "https://example.com/".to_owned()),
+                (
+                    Some("A chart".to_owned()),
+                    "https://example.com/a.png".to_owned()
+                ),
+            ]
+        );
+    }
+}
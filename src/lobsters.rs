@@ -5,7 +5,9 @@ use url::Url;
 use crate::Content;
 use crate::Post;
 use crate::PostThread;
+use crate::Reply;
 use crate::TextType;
+use crate::Timestamp;
 
 pub(crate) fn process(agent: &Agent, url: &mut Url) -> Option<anyhow::Result<Content>> {
     if !url.path().starts_with("/s/") {
@@ -29,14 +31,27 @@ pub(crate) fn process(agent: &Agent, url: &mut Url) -> Option<anyhow::Result<Con
                 author: story.submitter_user,
                 body: story.description_plain,
                 urls: vec![story.url],
+                timestamp: Some(story.created_at),
+                score: Some(story.score),
+                edited: false,
+                labels: vec![],
+                quote: None,
             },
             after: story
                 .comments
                 .into_iter()
-                .map(|c| Post {
-                    author: c.commenting_user,
-                    body: c.comment_plain,
-                    urls: vec![],
+                .map(|c| Reply {
+                    depth: c.depth,
+                    post: Post {
+                        author: c.commenting_user,
+                        body: c.comment_plain,
+                        urls: vec![],
+                        timestamp: Some(c.created_at),
+                        score: Some(c.score),
+                        edited: false,
+                        labels: vec![],
+                        quote: None,
+                    },
                 })
                 .collect(),
         })))
@@ -46,7 +61,9 @@ pub(crate) fn process(agent: &Agent, url: &mut Url) -> Option<anyhow::Result<Con
 #[derive(Debug, Deserialize)]
 struct Story {
     comments: Vec<Comment>,
+    created_at: Timestamp,
     description_plain: String,
+    score: i64,
     submitter_user: String,
     title: String,
     url: String,
@@ -54,6 +71,10 @@ struct Story {
 
 #[derive(Debug, Deserialize)]
 struct Comment {
+    #[expect(clippy::struct_field_names)]
     comment_plain: String,
     commenting_user: String,
+    created_at: Timestamp,
+    depth: usize,
+    score: i64,
 }
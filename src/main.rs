@@ -12,35 +12,47 @@ use zxcv::show_url;
 
 fn main() -> anyhow::Result<()> {
     let mut config_file = None;
+    let mut interactive = false;
+    let mut links = false;
 
     let mut args: Vec<String> = env::args().collect();
-    let mut opts = Parser::new(&args, "f:");
+    let mut opts = Parser::new(&args, "f:il");
     loop {
         match opts.next().transpose()? {
             None => break,
             Some(opt) => match opt {
                 Opt('f', Some(arg)) => config_file = Some(arg),
+                Opt('i', None) => interactive = true,
+                Opt('l', None) => links = true,
                 _ => unreachable!(),
             },
         }
     }
     args = args.split_off(opts.index());
 
-    let config = if let Some(config_file) = config_file {
+    let mut config = if let Some(config_file) = config_file {
         Config::from_toml(
             &std::fs::read_to_string(config_file).context("Failed to open config file")?,
         )?
     } else {
         Config::default()
     };
+    if interactive {
+        config.set_interactive(true);
+    }
+    if links {
+        config.set_links(true);
+    }
 
     let [url] = args.as_slice() else {
         bail!("One argument is required");
     };
 
-    pledge_promises!(Stdio Tmppath Inet Dns Proc Exec)
-        .or_else(pledge::Error::ignore_platform)
-        .expect("Initial pledge cannot fail");
+    match pledge_promises!(Stdio Tmppath Inet Dns Proc Exec) {
+        // The cache directory is outside of what the promises allow.
+        Ok(()) => zxcv::disable_cache(),
+        Err(e) => e.ignore_platform().expect("Initial pledge cannot fail"),
+    }
 
     show_url(&config, url)
 }
@@ -1,20 +1,30 @@
 use std::fmt::Write;
 
+use anyhow::Context;
 use scraper::Html;
 use serde::Deserialize;
 use ureq::Agent;
 use url::Url;
 
+use crate::Collection;
 use crate::Content;
+use crate::Gallery;
+use crate::Item;
 use crate::Post;
 use crate::PostThread;
+use crate::Reply;
 use crate::TextType;
+use crate::Timestamp;
+use crate::author_name;
 use crate::html;
+use crate::labeled_url;
+use crate::nest_replies;
 
 #[derive(Debug, PartialEq)]
 enum Path<'a> {
-    Profile { acct: &'a str },
+    Profile { acct: &'a str, with_replies: bool },
     Status { status_id: &'a str },
+    Tag { tag: &'a str },
 }
 
 fn parse_path(url: &Url) -> Option<Path<'_>> {
@@ -27,19 +37,46 @@ fn parse_path(url: &Url) -> Option<Path<'_>> {
         if path_segments.len() == 1
             && let Some(acct) = path_segments[0].strip_prefix('@')
         {
-            Path::Profile { acct }
+            Path::Profile {
+                acct,
+                with_replies: false,
+            }
+        } else if path_segments.len() == 2
+            && path_segments[1] == "with_replies"
+            && let Some(acct) = path_segments[0].strip_prefix('@')
+        {
+            Path::Profile {
+                acct,
+                with_replies: true,
+            }
+        } else if path_segments.len() == 2 && path_segments[0] == "tags" {
+            Path::Tag {
+                tag: path_segments[1],
+            }
         } else if path_segments.len() == 2
             && (path_segments[0] == "notice" || path_segments[0].starts_with('@'))
         {
             Path::Status {
                 status_id: path_segments[1],
             }
+        } else if (path_segments.len() == 3 && path_segments[0] == "p")
+            || (path_segments.len() == 4 && path_segments[..3] == ["i", "web", "post"])
+        {
+            // Pixelfed
+            Path::Status {
+                status_id: path_segments[path_segments.len() - 1],
+            }
         } else {
             return None;
         },
     )
 }
 
+/// Return whether `url` is a profile, status or tag.
+pub(crate) fn supports_path(url: &Url) -> bool {
+    parse_path(url).is_some()
+}
+
 pub(crate) fn try_process(
     agent: &Agent,
     url: &Url,
@@ -59,19 +96,29 @@ pub(crate) fn try_process(
     let is_pleroma = html::select_single_element(tree, "noscript")
         .is_some_and(|e| e.inner_html().contains("Pleroma"));
 
-    // Sharkey implements the Mastodon API.
-    let is_sharkey = html::select_single_element(tree, "meta[name=\"application-name\"]")
-        .is_some_and(|e| e.attr("content") == Some("Sharkey"));
-
-    if !(is_akkoma || is_iceshrimp || is_mastodon || is_pleroma || is_sharkey) {
+    if !(is_akkoma || is_iceshrimp || is_mastodon || is_pleroma) {
         return None;
     }
 
+    process(agent, url)
+}
+
+pub(crate) fn process(agent: &Agent, url: &Url) -> Option<anyhow::Result<Content>> {
+    process_api(agent, url, false)
+}
+
+/// Pixelfed implements the Mastodon API, but its statuses are mostly images so a status of just
+/// images is shown as those images with its text as the caption.
+pub(crate) fn process_pixelfed(agent: &Agent, url: &Url) -> Option<anyhow::Result<Content>> {
+    process_api(agent, url, true)
+}
+
+fn process_api(agent: &Agent, url: &Url, show_images: bool) -> Option<anyhow::Result<Content>> {
     let path = parse_path(url)?;
     let api_base = url.join("/api/v1/").expect("URL is valid");
 
     Some((|| match path {
-        Path::Profile { acct } => {
+        Path::Profile { acct, with_replies } => {
             let account: Account = agent
                 .get(api_base.join("accounts/lookup")?.as_str())
                 .query("acct", acct)
@@ -84,15 +131,16 @@ pub(crate) fn try_process(
                         .join(&format!("accounts/{}/statuses", account.id))?
                         .as_str(),
                 )
+                .query("exclude_replies", (!with_replies).to_string())
                 .call()?
                 .body_mut()
                 .read_json()?;
 
-            let mut body = html::render(&account.note, url);
+            let mut body = html::render(&replace_emojis(&account.note, &account.emojis), url);
             if !body.is_empty() && !account.fields.is_empty() {
                 body.push('\n');
             }
-            for field in account.fields {
+            for field in &account.fields {
                 write!(
                     body,
                     "\n{}: {}",
@@ -105,11 +153,11 @@ pub(crate) fn try_process(
                 title: None,
                 before: vec![],
                 main: Post {
-                    author: account.display_name,
+                    author: account.name(),
                     body,
-                    urls: vec![],
+                    ..Post::default()
                 },
-                after: statuses.into_iter().map(|s| s.render(url)).collect(),
+                after: statuses.into_iter().map(|s| s.render(url).into()).collect(),
             })))
         }
 
@@ -119,40 +167,194 @@ pub(crate) fn try_process(
                 .call()?
                 .body_mut()
                 .read_json()?;
-            let context: StatusContext = agent
-                .get(
-                    api_base
-                        .join(&format!("statuses/{status_id}/context"))?
-                        .as_str(),
-                )
+
+            if show_images && let Some(content) = direct_images(agent, url, &status) {
+                return content;
+            }
+
+            // A remote status as seen from another instance may lack replies the origin has.
+            if let Some((origin_api_base, origin_id)) = find_origin(url, &status)
+                && let Ok((status, context)) = get_thread(agent, &origin_api_base, &origin_id, None)
+            {
+                return Ok(render_thread(status, context, url));
+            }
+
+            let (status, context) = get_thread(agent, &api_base, status_id, Some(status))?;
+            Ok(render_thread(status, context, url))
+        }
+
+        Path::Tag { tag } => {
+            let statuses: Vec<Status> = agent
+                .get(api_base.join(&format!("timelines/tag/{tag}"))?.as_str())
                 .call()?
                 .body_mut()
                 .read_json()?;
 
-            Ok(Content::Text(TextType::PostThread(PostThread {
-                title: None,
-                before: context
-                    .ancestors
+            Ok(Content::Collection(Collection {
+                title: Some(format!(
+                    "#{}",
+                    percent_encoding::percent_decode_str(tag).decode_utf8()?
+                )),
+                description: None,
+                items: statuses
                     .into_iter()
-                    .map(|s| s.render(url))
+                    .map(|s| {
+                        let status_url = s.url.clone().unwrap_or_else(|| s.uri.clone());
+                        let post = s.render(url);
+                        Item {
+                            title: Some(post.author),
+                            url: status_url,
+                            description: Some(post.body),
+                        }
+                    })
                     .collect(),
-                main: status.render(url),
-                after: context
-                    .descendants
-                    .into_iter()
-                    .map(|s| s.render(url))
-                    .collect(),
-            })))
+            }))
         }
     })())
 }
 
+fn direct_images(agent: &Agent, url: &Url, status: &Status) -> Option<anyhow::Result<Content>> {
+    if status.media_attachments.is_empty()
+        || status
+            .media_attachments
+            .iter()
+            .any(|a| a.kind != MediaType::Image)
+    {
+        return None;
+    }
+
+    Some((|| {
+        let urls = status
+            .media_attachments
+            .iter()
+            .map(|a| Url::parse(&a.url))
+            .collect::<Result<Vec<_>, _>>()
+            .context("Server returned invalid URL")?;
+        let caption = Some(html::render(&status.content, url)).filter(|c| !c.is_empty());
+        Ok(Content::Gallery(Gallery::download(agent, &urls, caption)?))
+    })())
+}
+
+/// Return the API base and ID of `status` on the server it originates from if that isn't the
+/// server of `url`.
+fn find_origin(url: &Url, status: &Status) -> Option<(Url, String)> {
+    let origin = Url::parse(status.url.as_deref()?).ok()?;
+    if origin.host_str() == url.host_str() {
+        return None;
+    }
+
+    // Mastodon's status URLs and URIs have the ID, but other servers' may not. Searching for the
+    // URI instead would need an account on the origin server.
+    let status_id = if let Some(Path::Status { status_id }) = parse_path(&origin) {
+        status_id.to_owned()
+    } else {
+        let uri = Url::parse(&status.uri).ok()?;
+        if uri.host_str() != origin.host_str() {
+            return None;
+        }
+        match uri.path_segments()?.collect::<Vec<_>>().as_slice() {
+            ["users", _, "statuses", status_id] => (*status_id).to_owned(),
+            _ => return None,
+        }
+    };
+    Some((origin.join("/api/v1/").ok()?, status_id))
+}
+
+/// Fetch the status `status_id` unless given as `status` along with its context.
+fn get_thread(
+    agent: &Agent,
+    api_base: &Url,
+    status_id: &str,
+    status: Option<Status>,
+) -> anyhow::Result<(Status, StatusContext)> {
+    let status = match status {
+        Some(status) => status,
+        None => agent
+            .get(api_base.join(&format!("statuses/{status_id}"))?.as_str())
+            .call()?
+            .body_mut()
+            .read_json()?,
+    };
+    let context: StatusContext = agent
+        .get(
+            api_base
+                .join(&format!("statuses/{status_id}/context"))?
+                .as_str(),
+        )
+        .call()?
+        .body_mut()
+        .read_json()?;
+    Ok((status, context))
+}
+
+fn render_thread(status: Status, context: StatusContext, url: &Url) -> Content {
+    Content::Text(TextType::PostThread(PostThread {
+        title: None,
+        before: context
+            .ancestors
+            .into_iter()
+            .map(|s| s.render(url))
+            .collect(),
+        main: status.render(url),
+        after: nest_replies(
+            context.descendants,
+            |s| s.id.clone(),
+            |s| s.in_reply_to_id.clone(),
+        )
+        .into_iter()
+        .map(|(depth, s)| Reply {
+            depth,
+            post: s.render(url),
+        })
+        .collect(),
+    }))
+}
+
 #[derive(Debug, Deserialize)]
 struct Account {
+    acct: String,
     display_name: String,
+    #[serde(default)]
+    emojis: Vec<CustomEmoji>,
     fields: Vec<Field>,
     id: String,
     note: String,
+    url: String,
+}
+
+impl Account {
+    fn name(&self) -> String {
+        // Local accounts omit the instance, which is where their profile is.
+        let handle = match Url::parse(&self.url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_owned))
+        {
+            Some(host) if !self.acct.contains('@') => format!("{}@{host}", self.acct),
+            _ => self.acct.clone(),
+        };
+        author_name(&replace_emojis(&self.display_name, &self.emojis), &handle)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Card {
+    title: String,
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CustomEmoji {
+    shortcode: String,
+}
+
+/// Replace `:shortcode:` names of `emojis` in `text` with the words of the shortcode.
+fn replace_emojis(text: &str, emojis: &[CustomEmoji]) -> String {
+    emojis.iter().fold(text.to_owned(), |text, emoji| {
+        text.replace(
+            &format!(":{}:", emoji.shortcode),
+            &format!("[{}]", emoji.shortcode.replace(['_', '-'], " ")),
+        )
+    })
 }
 
 #[derive(Debug, Deserialize)]
@@ -163,26 +365,121 @@ struct Field {
 
 #[derive(Debug, Deserialize)]
 struct Status {
+    card: Option<Card>,
     content: String,
+    created_at: Timestamp,
+    edited_at: Option<Timestamp>,
+    #[serde(default)]
+    emojis: Vec<CustomEmoji>,
+    favourites_count: i64,
+    id: String,
+    in_reply_to_id: Option<String>,
     account: Account,
     media_attachments: Vec<MediaAttachment>,
+    poll: Option<Poll>,
+    reblog: Option<Box<Self>>,
+    #[serde(default)]
+    spoiler_text: String,
+    uri: String,
+    url: Option<String>,
 }
 
 impl Status {
     fn render(self, url: &Url) -> Post {
+        // A boost has no content of its own.
+        if let Some(reblog) = self.reblog {
+            let mut post = reblog.render(url);
+            post.labels
+                .push(format!("boosted by {}", self.account.name()));
+            return post;
+        }
+
+        let mut body = String::new();
+        if !self.spoiler_text.is_empty() {
+            body = format!(
+                "CW: {}\n\n",
+                replace_emojis(&self.spoiler_text, &self.emojis)
+            );
+        }
+        body.push_str(&html::render(
+            &replace_emojis(&self.content, &self.emojis),
+            url,
+        ));
+        if let Some(poll) = self.poll {
+            if !body.is_empty() {
+                body.push_str("\n\n");
+            }
+            body.push_str(&poll.render(&self.emojis));
+        }
+
+        let mut urls: Vec<_> = self
+            .media_attachments
+            .into_iter()
+            .map(|a| labeled_url(a.description.as_deref().unwrap_or_default(), a.url))
+            .collect();
+        urls.extend(self.card.map(|c| labeled_url(&c.title, c.url)));
+
         Post {
-            author: self.account.display_name,
-            body: html::render(&self.content, url),
-            urls: self.media_attachments.into_iter().map(|a| a.url).collect(),
+            author: self.account.name(),
+            body,
+            urls,
+            timestamp: Some(self.created_at),
+            score: Some(self.favourites_count),
+            edited: self.edited_at.is_some(),
+            labels: vec![],
+            quote: None,
         }
     }
 }
 
 #[derive(Debug, Deserialize)]
 struct MediaAttachment {
+    description: Option<String>,
+    #[serde(rename = "type")]
+    kind: MediaType,
     url: String,
 }
 
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum MediaType {
+    Image,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct Poll {
+    expired: bool,
+    options: Vec<PollOption>,
+    votes_count: u64,
+}
+
+impl Poll {
+    fn render(&self, emojis: &[CustomEmoji]) -> String {
+        let mut text = format!(
+            "Poll, {} votes{}:",
+            self.votes_count,
+            if self.expired { ", closed" } else { "" }
+        );
+        for option in &self.options {
+            write!(text, "\n* {}", replace_emojis(&option.title, emojis))
+                .expect("write into String can't fail");
+            // Servers may hide counts until the poll closes.
+            if let Some(votes_count) = option.votes_count {
+                write!(text, " ({votes_count})").expect("write into String can't fail");
+            }
+        }
+        text
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PollOption {
+    title: String,
+    votes_count: Option<u64>,
+}
+
 #[derive(Debug, Deserialize)]
 struct StatusContext {
     ancestors: Vec<Status>,
@@ -191,8 +488,203 @@ struct StatusContext {
 
 #[cfg(test)]
 mod tests {
+    use ureq::Agent;
+    use url::Url;
+
     use super::Path;
+    use super::Status;
+    use super::find_origin;
+    use super::get_thread;
     use crate::tests::parse_path_tests;
+    use crate::tests::serve_json;
+
+    /// Return the JSON of a status with `id`, `uri` and `url`.
+    fn status_json(id: &str, uri: &str, url: &str) -> String {
+        format!(
+            r#"{{
+                "id": "{id}",
+                "uri": "{uri}",
+                "url": "{url}",
+                "content": "<p>Status {id}</p>",
+                "created_at": "2024-01-01T00:00:00Z",
+                "edited_at": null,
+                "favourites_count": 0,
+                "in_reply_to_id": null,
+                "account": {{
+                    "id": "1",
+                    "acct": "author",
+                    "url": "https://example.com/@author",
+                    "display_name": "Author",
+                    "fields": [],
+                    "note": ""
+                }},
+                "media_attachments": []
+            }}"#
+        )
+    }
+
+    fn parse_status(uri: &str, url: &str) -> Status {
+        serde_json::from_str(&status_json("1", uri, url)).unwrap()
+    }
+
+    #[test]
+    fn origin() {
+        let url = Url::parse("https://example.com/@author@example.org/1").unwrap();
+        assert_eq!(
+            find_origin(
+                &url,
+                &parse_status(
+                    "https://example.org/users/author/statuses/5",
+                    "https://example.org/@author/5"
+                )
+            ),
+            Some((
+                Url::parse("https://example.org/api/v1/").unwrap(),
+                "5".to_owned()
+            ))
+        );
+        // The URL doesn't have the ID but the URI does.
+        assert_eq!(
+            find_origin(
+                &url,
+                &parse_status(
+                    "https://example.org/users/author/statuses/5",
+                    "https://example.org/display/5"
+                )
+            ),
+            Some((
+                Url::parse("https://example.org/api/v1/").unwrap(),
+                "5".to_owned()
+            ))
+        );
+        assert_eq!(
+            find_origin(
+                &url,
+                &parse_status(
+                    "https://example.org/objects/abc",
+                    "https://example.org/notes/abc"
+                )
+            ),
+            None
+        );
+        assert_eq!(
+            find_origin(
+                &url,
+                &parse_status(
+                    "https://example.com/users/author/statuses/1",
+                    "https://example.com/@author/1"
+                )
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn thread() {
+        let context = format!(
+            r#"{{"ancestors": [{}], "descendants": [{}]}}"#,
+            status_json("1", "https://example.com/1", "https://example.com/1"),
+            status_json("3", "https://example.com/3", "https://example.com/3"),
+        );
+        let agent = Agent::new_with_defaults();
+
+        let base = serve_json(vec![
+            (
+                "/api/v1/statuses/2",
+                status_json("2", "https://example.com/2", "https://example.com/2"),
+            ),
+            ("/api/v1/statuses/2/context", context.clone()),
+        ]);
+        let (status, context_) =
+            get_thread(&agent, &base.join("/api/v1/").unwrap(), "2", None).unwrap();
+        assert_eq!(status.id, "2");
+        assert_eq!(context_.ancestors[0].id, "1");
+        assert_eq!(context_.descendants[0].id, "3");
+
+        // A given status isn't fetched again.
+        let base = serve_json(vec![("/api/v1/statuses/2/context", context)]);
+        let given = serde_json::from_str(&status_json(
+            "2",
+            "https://example.com/2",
+            "https://example.com/2",
+        ))
+        .unwrap();
+        let (status, _) =
+            get_thread(&agent, &base.join("/api/v1/").unwrap(), "2", Some(given)).unwrap();
+        assert_eq!(status.id, "2");
+    }
+
+    #[test]
+    fn render_status() {
+        let status: Status = serde_json::from_str(
+            r#"{
+                "id": "2",
+                "uri": "https://example.org/users/booster/statuses/2/activity",
+                "content": "",
+                "created_at": "2024-01-02T00:00:00Z",
+                "edited_at": null,
+                "favourites_count": 0,
+                "in_reply_to_id": null,
+                "account": {
+                    "id": "2",
+                    "acct": "booster@example.org",
+                    "url": "https://example.org/@booster",
+                    "display_name": "Booster",
+                    "fields": [],
+                    "note": ""
+                },
+                "media_attachments": [],
+                "reblog": {
+                    "id": "1",
+                    "uri": "https://example.com/users/author/statuses/1",
+                    "content": "<p>Vote :blob_cat:</p>",
+                    "spoiler_text": "politics",
+                    "created_at": "2024-01-01T00:00:00Z",
+                    "edited_at": null,
+                    "favourites_count": 3,
+                    "in_reply_to_id": null,
+                    "account": {
+                        "id": "1",
+                        "acct": "author",
+                        "url": "https://example.com/@author",
+                        "display_name": "Author :verified:",
+                        "emojis": [{"shortcode": "verified"}],
+                        "fields": [],
+                        "note": ""
+                    },
+                    "emojis": [{"shortcode": "blob_cat"}],
+                    "media_attachments": [
+                        {"type": "image", "url": "https://example.com/a.png", "description": "A chart"}
+                    ],
+                    "card": {"url": "https://example.com/", "title": "Example"},
+                    "poll": {
+                        "expired": true,
+                        "votes_count": 5,
+                        "options": [
+                            {"title": "Yes", "votes_count": 4},
+                            {"title": "No", "votes_count": 1}
+                        ]
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        let post = status.render(&Url::parse("https://example.com/").unwrap());
+
+        assert_eq!(post.author, "Author [verified] (@author@example.com)");
+        assert_eq!(
+            post.body,
+            "CW: politics\n\nVote [blob cat]\n\nPoll, 5 votes, closed:\n* Yes (4)\n* No (1)"
+        );
+        assert_eq!(
+            post.urls,
+            [
+                "A chart: https://example.com/a.png",
+                "Example: https://example.com/"
+            ]
+        );
+        assert_eq!(post.labels, ["boosted by Booster (@booster@example.org)"]);
+    }
 
     parse_path_tests!(
         super::parse_path,
@@ -207,7 +699,25 @@ mod tests {
         (
             profile,
             "/@example",
-            Some(Path::Profile { acct: "example" })
+            Some(Path::Profile {
+                acct: "example",
+                with_replies: false
+            })
+        ),
+        (
+            profile_with_replies,
+            "/@example/with_replies",
+            Some(Path::Profile {
+                acct: "example",
+                with_replies: true
+            })
+        ),
+        (
+            remote_status,
+            "/@example@example.org/17291729",
+            Some(Path::Status {
+                status_id: "17291729"
+            })
         ),
         (
             status,
@@ -216,6 +726,21 @@ mod tests {
                 status_id: "17291729"
             })
         ),
+        (
+            pixelfed_status,
+            "/p/example/17291729",
+            Some(Path::Status {
+                status_id: "17291729"
+            })
+        ),
+        (
+            pixelfed_web_status,
+            "/i/web/post/17291729",
+            Some(Path::Status {
+                status_id: "17291729"
+            })
+        ),
+        (tag, "/tags/rust", Some(Path::Tag { tag: "rust" })),
         (unknown, "/unknown", None),
     );
 }
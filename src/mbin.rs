@@ -0,0 +1,320 @@
+use serde::Deserialize;
+use ureq::Agent;
+use url::Url;
+
+use crate::Collection;
+use crate::Content;
+use crate::Item;
+use crate::Post;
+use crate::PostThread;
+use crate::Reply;
+use crate::TextType;
+use crate::Timestamp;
+use crate::html;
+
+// How deeply nested the comments returned with an entry or comment are.
+const COMMENT_DEPTH: &str = "10";
+
+#[derive(Debug, PartialEq)]
+enum Path<'a> {
+    Comment { comment_id: &'a str },
+    Entry { entry_id: &'a str },
+    Magazine { magazine: &'a str },
+}
+
+fn parse_path(url: &Url) -> Option<Path<'_>> {
+    let path_segments: Vec<_> = url
+        .path_segments()
+        .unwrap_or_else(|| "".split('/'))
+        .collect();
+
+    Some(match path_segments.as_slice() {
+        ["m", magazine] => Path::Magazine { magazine },
+        ["m", _, "t", _, _, "comment", comment_id, ..] => Path::Comment { comment_id },
+        ["m", _, "t", entry_id, ..] => Path::Entry { entry_id },
+        _ => return None,
+    })
+}
+
+/// Return whether `url` is a comment, entry or magazine.
+pub(crate) fn supports_path(url: &Url) -> bool {
+    parse_path(url).is_some()
+}
+
+pub(crate) fn process(agent: &Agent, url: &Url) -> Option<anyhow::Result<Content>> {
+    let path = parse_path(url)?;
+    let api_base = url.join("/api/").expect("URL is valid");
+
+    Some((|| match path {
+        Path::Comment { comment_id } => {
+            let mut comment: Comment = agent
+                .get(api_base.join(&format!("comments/{comment_id}"))?.as_str())
+                .query("d", COMMENT_DEPTH)
+                .call()?
+                .body_mut()
+                .read_json()?;
+            let entry = get_entry(agent, &api_base, &comment.entry_id.to_string())?;
+
+            let mut after = vec![];
+            render_comments(std::mem::take(&mut comment.children), 0, url, &mut after);
+            Ok(Content::Text(TextType::PostThread(PostThread {
+                title: Some(entry.title.clone()),
+                before: vec![entry.render(url)],
+                main: comment.render(url),
+                after,
+            })))
+        }
+
+        Path::Entry { entry_id } => {
+            let entry = get_entry(agent, &api_base, entry_id)?;
+            let comments: List<Comment> = agent
+                .get(
+                    api_base
+                        .join(&format!("entry/{entry_id}/comments"))?
+                        .as_str(),
+                )
+                .query("sortBy", "hot")
+                .query("d", COMMENT_DEPTH)
+                .query("perPage", "100")
+                .call()?
+                .body_mut()
+                .read_json()?;
+
+            let mut after = vec![];
+            render_comments(comments.items, 0, url, &mut after);
+            Ok(Content::Text(TextType::PostThread(PostThread {
+                title: Some(entry.title.clone()),
+                before: vec![],
+                main: entry.render(url),
+                after,
+            })))
+        }
+
+        Path::Magazine { magazine } => {
+            let magazine: Magazine = agent
+                .get(
+                    api_base
+                        .join(&format!("magazine/name/{magazine}"))?
+                        .as_str(),
+                )
+                .call()?
+                .body_mut()
+                .read_json()?;
+            let entries: List<Entry> = agent
+                .get(
+                    api_base
+                        .join(&format!("magazine/{}/entries", magazine.id))?
+                        .as_str(),
+                )
+                .query("sort", "newest")
+                .query("perPage", "20")
+                .call()?
+                .body_mut()
+                .read_json()?;
+
+            Ok(Content::Collection(Collection {
+                title: Some(magazine.title),
+                description: magazine.description.map(|d| html::render_markdown(&d, url)),
+                items: entries
+                    .items
+                    .into_iter()
+                    .map(|e| Item {
+                        title: Some(e.title),
+                        url: url
+                            .join(&format!("/m/{}/t/{}", magazine.name, e.id))
+                            .map_or(e.ap_id.unwrap_or_default(), Into::into),
+                        description: Some(e.user.name(url)),
+                    })
+                    .collect(),
+            }))
+        }
+    })())
+}
+
+fn get_entry(agent: &Agent, api_base: &Url, entry_id: &str) -> anyhow::Result<Entry> {
+    Ok(agent
+        .get(api_base.join(&format!("entry/{entry_id}"))?.as_str())
+        .call()?
+        .body_mut()
+        .read_json()?)
+}
+
+/// Append `comments` and their children, which the API nests, to `replies` in order.
+fn render_comments(comments: Vec<Comment>, depth: usize, url: &Url, replies: &mut Vec<Reply>) {
+    for mut comment in comments {
+        let children = std::mem::take(&mut comment.children);
+        replies.push(Reply {
+            depth,
+            post: comment.render(url),
+        });
+        render_comments(children, depth + 1, url, replies);
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Comment {
+    body: Option<String>,
+    #[serde(default)]
+    children: Vec<Self>,
+    created_at: Timestamp,
+    #[serde(default)]
+    dv: i64,
+    edited_at: Option<Timestamp>,
+    entry_id: u64,
+    #[serde(default)]
+    favourites: i64,
+    user: User,
+    visibility: Option<String>,
+}
+
+impl Comment {
+    fn render(self, url: &Url) -> Post {
+        Post {
+            author: self.user.name(url),
+            body: html::render_markdown(self.body.as_deref().unwrap_or_default(), url),
+            urls: vec![],
+            timestamp: Some(self.created_at),
+            // Favourites are Mbin's upvotes; its boosts are shares rather than votes.
+            score: Some(self.favourites - self.dv),
+            edited: self.edited_at.is_some(),
+            labels: visibility_labels(self.visibility),
+            quote: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    ap_id: Option<String>,
+    body: Option<String>,
+    created_at: Timestamp,
+    #[serde(default)]
+    dv: i64,
+    edited_at: Option<Timestamp>,
+    #[serde(default)]
+    favourites: i64,
+    #[serde(rename = "entryId")]
+    id: u64,
+    title: String,
+    url: Option<String>,
+    user: User,
+    visibility: Option<String>,
+}
+
+impl Entry {
+    fn render(self, url: &Url) -> Post {
+        Post {
+            author: self.user.name(url),
+            body: html::render_markdown(self.body.as_deref().unwrap_or_default(), url),
+            urls: self.url.into_iter().collect(),
+            timestamp: Some(self.created_at),
+            score: Some(self.favourites - self.dv),
+            edited: self.edited_at.is_some(),
+            labels: visibility_labels(self.visibility),
+            quote: None,
+        }
+    }
+}
+
+/// Return the labels for a post with `visibility`, which is normally `visible`.
+fn visibility_labels(visibility: Option<String>) -> Vec<String> {
+    visibility
+        .filter(|v| v != "visible")
+        .map(|v| v.replace('_', " "))
+        .into_iter()
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+struct List<T> {
+    items: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Magazine {
+    description: Option<String>,
+    #[serde(rename = "magazineId")]
+    id: u64,
+    name: String,
+    title: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct User {
+    username: String,
+}
+
+impl User {
+    /// Return the user's handle. Remote users' usernames include their host, and local users'
+    /// don't.
+    fn name(&self, url: &Url) -> String {
+        let username = self.username.trim_start_matches('@');
+        match url.host_str() {
+            Some(host) if !username.contains('@') => format!("{username}@{host}"),
+            _ => username.to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::Path;
+    use super::User;
+    use super::visibility_labels;
+    use crate::tests::parse_path_tests;
+
+    parse_path_tests!(
+        super::parse_path,
+        "https://example.com{}",
+        (
+            magazine,
+            "/m/rust",
+            Some(Path::Magazine { magazine: "rust" })
+        ),
+        (
+            entry,
+            "/m/rust/t/12345/a-title",
+            Some(Path::Entry { entry_id: "12345" })
+        ),
+        (
+            entry_comments,
+            "/m/rust/t/12345/a-title/comments",
+            Some(Path::Entry { entry_id: "12345" })
+        ),
+        (
+            comment,
+            "/m/rust/t/12345/a-title/comment/678",
+            Some(Path::Comment { comment_id: "678" })
+        ),
+        (microblog, "/m/rust/p/12345", None),
+        (unknown, "/u/example", None),
+    );
+
+    #[test]
+    fn user_name() {
+        let url = Url::parse("https://example.com/m/rust").unwrap();
+        let user = |username: &str| User {
+            username: username.to_owned(),
+        };
+        assert_eq!(user("example").name(&url), "example@example.com");
+        assert_eq!(
+            user("@example@example.org").name(&url),
+            "example@example.org"
+        );
+    }
+
+    #[test]
+    fn visibility() {
+        assert!(visibility_labels(Some("visible".to_owned())).is_empty());
+        assert_eq!(
+            visibility_labels(Some("soft_deleted".to_owned())),
+            ["soft deleted"]
+        );
+        assert!(visibility_labels(None).is_empty());
+    }
+}
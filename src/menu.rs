@@ -0,0 +1,110 @@
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+use std::process::Command;
+use std::process::Stdio;
+
+use crate::Config;
+use crate::Item;
+
+/// Ask the user to choose one of `items` and return its index or `None` if they chose none.
+///
+/// The items are numbered and given to the configured menu command one per line, or listed on
+/// the terminal with a prompt for the number if there is no menu command.
+pub(crate) fn choose(config: &Config, items: &[Item]) -> anyhow::Result<Option<usize>> {
+    let lines: Vec<_> = items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| line(i + 1, item))
+        .collect();
+
+    let choice = match config.get_menu_argv() {
+        [] => prompt(&lines)?,
+        argv => run_menu(argv, &lines)?,
+    };
+
+    Ok(choice
+        .as_deref()
+        .and_then(parse_choice)
+        .filter(|n| (1..=items.len()).contains(n))
+        .map(|n| n - 1))
+}
+
+fn line(number: usize, item: &Item) -> String {
+    let line = match &item.title {
+        Some(title) => format!("{number}. {title}: {}", item.url),
+        None => format!("{number}. {}", item.url),
+    };
+    // Each item must be a single line for the menu.
+    line.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Return the number of the item on a line chosen from the menu.
+fn parse_choice(line: &str) -> Option<usize> {
+    let line = line.trim();
+    line.split_once('.').map_or(line, |(n, _)| n).parse().ok()
+}
+
+fn prompt(lines: &[String]) -> io::Result<Option<String>> {
+    let mut stdout = io::stdout().lock();
+    for line in lines {
+        writeln!(stdout, "{line}")?;
+    }
+    write!(stdout, "Item (empty to quit): ")?;
+    stdout.flush()?;
+
+    let mut choice = String::new();
+    io::stdin().lock().read_line(&mut choice)?;
+    Ok(Some(choice).filter(|c| !c.trim().is_empty()))
+}
+
+fn run_menu(argv: &[String], lines: &[String]) -> io::Result<Option<String>> {
+    let mut child = Command::new(&argv[0])
+        .args(&argv[1..])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+
+    {
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        for line in lines {
+            // The menu may exit before reading every line.
+            if writeln!(stdin, "{line}").is_err() {
+                break;
+            }
+        }
+    }
+
+    let output = child.wait_with_output()?;
+    // Menus exit non-zero when the user cancels.
+    Ok(Some(String::from_utf8_lossy(&output.stdout).into_owned())
+        .filter(|_| output.status.success()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::line;
+    use super::parse_choice;
+    use crate::Item;
+
+    #[test]
+    fn lines() {
+        let item = |title: Option<&str>| Item {
+            title: title.map(ToOwned::to_owned),
+            url: "https://example.com/".to_owned(),
+            description: None,
+        };
+        assert_eq!(
+            line(1, &item(Some("A\ntitle"))),
+            "1. A title: https://example.com/"
+        );
+        assert_eq!(line(12, &item(None)), "12. https://example.com/");
+    }
+
+    #[test]
+    fn choices() {
+        assert_eq!(parse_choice("3. Title: https://example.com/\n"), Some(3));
+        assert_eq!(parse_choice(" 12\n"), Some(12));
+        assert_eq!(parse_choice("Title"), None);
+    }
+}
@@ -0,0 +1,486 @@
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+use scraper::Html;
+use serde::Deserialize;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use ureq::Agent;
+use url::Url;
+
+use crate::Content;
+use crate::Post;
+use crate::PostThread;
+use crate::Quote;
+use crate::Reply;
+use crate::TextType;
+use crate::Timestamp;
+use crate::author_name;
+use crate::html;
+use crate::labeled_url;
+use crate::nest_replies;
+
+// Inline formatting tags of MFM which are dropped leaving their content.
+const MFM_TAGS: &[&str] = &["b", "center", "i", "plain", "s", "small"];
+
+#[derive(Debug, PartialEq)]
+enum Path<'a> {
+    Note {
+        note_id: &'a str,
+    },
+    User {
+        username: &'a str,
+        host: Option<&'a str>,
+    },
+}
+
+fn parse_path(url: &Url) -> Option<Path<'_>> {
+    let path_segments: Vec<_> = url
+        .path_segments()
+        .unwrap_or_else(|| "".split('/'))
+        .collect();
+
+    Some(if path_segments.len() == 2 && path_segments[0] == "notes" {
+        Path::Note {
+            note_id: path_segments[1],
+        }
+    } else if path_segments.len() == 1
+        && let Some(acct) = path_segments[0].strip_prefix('@')
+    {
+        let (username, host) = acct
+            .split_once('@')
+            .map_or((acct, None), |(u, h)| (u, Some(h)));
+        Path::User { username, host }
+    } else {
+        return None;
+    })
+}
+
+/// Return whether `url` is a note or user.
+pub(crate) fn supports_path(url: &Url) -> bool {
+    parse_path(url).is_some()
+}
+
+pub(crate) fn try_process(
+    agent: &Agent,
+    url: &Url,
+    tree: &Html,
+) -> Option<anyhow::Result<Content>> {
+    // Forks keep Misskey's API but change the name.
+    if !html::select_single_element(tree, "meta[name=\"application-name\"]")
+        .and_then(|e| e.attr("content"))
+        .is_some_and(|c| matches!(c, "CherryPick" | "Firefish" | "Misskey" | "Sharkey"))
+    {
+        return None;
+    }
+
+    process(agent, url)
+}
+
+pub(crate) fn process(agent: &Agent, url: &Url) -> Option<anyhow::Result<Content>> {
+    let path = parse_path(url)?;
+
+    Some((|| match path {
+        Path::Note { note_id } => {
+            let mut note: Note = call(agent, url, "notes/show", &NoteRequest { note_id })?;
+            let parent = note.reply.take();
+            // The conversation is the note's ancestors starting from its parent.
+            let mut ancestors: Vec<Note> = if parent.is_some() {
+                call(
+                    agent,
+                    url,
+                    "notes/conversation",
+                    &NoteListRequest {
+                        note_id,
+                        limit: 100,
+                    },
+                )?
+            } else {
+                vec![]
+            };
+            if ancestors.is_empty() {
+                ancestors.extend(parent.map(|p| *p));
+            }
+            let replies = get_replies(agent, url, note_id)?;
+
+            Ok(Content::Text(TextType::PostThread(PostThread {
+                title: None,
+                before: ancestors.into_iter().rev().map(|n| n.render(url)).collect(),
+                main: note.render(url),
+                after: nest_replies(replies, |r| r.id.clone(), |r| r.reply_id.clone())
+                    .into_iter()
+                    .map(|(depth, r)| Reply {
+                        depth,
+                        post: r.render(url),
+                    })
+                    .collect(),
+            })))
+        }
+
+        Path::User { username, host } => {
+            let user: User = call(agent, url, "users/show", &UserRequest { username, host })?;
+            let notes: Vec<Note> = call(
+                agent,
+                url,
+                "users/notes",
+                &UserNotesRequest {
+                    user_id: &user.id,
+                    limit: 20,
+                },
+            )?;
+
+            Ok(Content::Text(TextType::PostThread(PostThread {
+                title: None,
+                before: vec![],
+                main: Post {
+                    author: user.name(url),
+                    body: render_mfm(user.description.as_deref().unwrap_or_default()),
+                    ..Post::default()
+                },
+                after: notes.into_iter().map(|n| n.render(url).into()).collect(),
+            })))
+        }
+    })())
+}
+
+/// Make a request to the API `endpoint` of the server of `url`. Unlike most APIs, Misskey's
+/// takes arguments as a JSON body.
+fn call<T: DeserializeOwned>(
+    agent: &Agent,
+    url: &Url,
+    endpoint: &str,
+    request: &impl Serialize,
+) -> anyhow::Result<T> {
+    Ok(agent
+        .post(url.join(&format!("/api/{endpoint}"))?.as_str())
+        .send_json(request)?
+        .body_mut()
+        .read_json()?)
+}
+
+/// Return the replies to the note `note_id` and, up to a limit, the replies to those in turn.
+fn get_replies(agent: &Agent, url: &Url, note_id: &str) -> anyhow::Result<Vec<Note>> {
+    // Each note with replies costs a request.
+    const MAX_REQUESTS: usize = 20;
+
+    let mut replies = vec![];
+    let mut queue = VecDeque::from([note_id.to_owned()]);
+    for _ in 0..MAX_REQUESTS {
+        let Some(parent_id) = queue.pop_front() else {
+            break;
+        };
+        let children: Vec<Note> = call(
+            agent,
+            url,
+            "notes/children",
+            &NoteListRequest {
+                note_id: &parent_id,
+                limit: 100,
+            },
+        )?;
+        // Quotes are children too, but aren't replies.
+        for child in children
+            .into_iter()
+            .filter(|c| c.reply_id.as_deref() == Some(&parent_id))
+        {
+            if child.replies_count > 0 {
+                queue.push_back(child.id.clone());
+            }
+            replies.push(child);
+        }
+    }
+    Ok(replies)
+}
+
+/// Render MFM, Misskey's markup, as plain text.
+///
+/// Markdown-like syntax is left alone as it reads fine, but animation functions such as
+/// `$[spin text]` and tags like `<small>` are removed leaving their content, and custom emoji
+/// become their name in brackets.
+fn render_mfm(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut open_functions = 0;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if let Some(function) = rest.strip_prefix("$[") {
+            // The name and any arguments are separated from the content by a space.
+            if let Some((_, content)) = function.split_once(' ')
+                && function
+                    .split(' ')
+                    .next()
+                    .is_some_and(|n| !n.is_empty() && !n.contains(']'))
+            {
+                open_functions += 1;
+                rest = content;
+                continue;
+            }
+        } else if c == ']' && open_functions > 0 {
+            open_functions -= 1;
+            rest = &rest[1..];
+            continue;
+        } else if c == '<'
+            && let Some(tag) = MFM_TAGS.iter().find_map(|t| {
+                [format!("<{t}>"), format!("</{t}>")]
+                    .into_iter()
+                    .find(|tag| rest.starts_with(tag.as_str()))
+            })
+        {
+            rest = &rest[tag.len()..];
+            continue;
+        } else if c == ':'
+            && let Some((name, _)) = rest[1..].split_once(':')
+            && name.bytes().any(|b| b.is_ascii_alphabetic())
+            && name
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-' | b'@' | b'.'))
+            && !result.ends_with(|p: char| p.is_alphanumeric())
+        {
+            result.push('[');
+            result.push_str(&name.replace(['_', '-'], " "));
+            result.push(']');
+            rest = &rest[name.len() + 2..];
+            continue;
+        }
+        result.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    result
+}
+
+#[derive(Debug, Serialize)]
+struct NoteRequest<'a> {
+    #[serde(rename = "noteId")]
+    note_id: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct NoteListRequest<'a> {
+    #[serde(rename = "noteId")]
+    note_id: &'a str,
+    limit: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct UserRequest<'a> {
+    username: &'a str,
+    host: Option<&'a str>,
+}
+
+#[derive(Debug, Serialize)]
+struct UserNotesRequest<'a> {
+    #[serde(rename = "userId")]
+    user_id: &'a str,
+    limit: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct DriveFile {
+    comment: Option<String>,
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Note {
+    created_at: Timestamp,
+    cw: Option<String>,
+    #[serde(default)]
+    files: Vec<DriveFile>,
+    id: String,
+    #[serde(default)]
+    reactions: HashMap<String, i64>,
+    renote: Option<Box<Self>>,
+    #[serde(default)]
+    replies_count: u64,
+    reply: Option<Box<Self>>,
+    reply_id: Option<String>,
+    text: Option<String>,
+    user: User,
+}
+
+impl Note {
+    fn render(self, url: &Url) -> Post {
+        let author = self.user.name(url);
+
+        // A renote without text of its own is a boost rather than a quote.
+        if self.text.is_none()
+            && self.files.is_empty()
+            && let Some(renote) = self.renote
+        {
+            let mut post = renote.render(url);
+            post.labels.push(format!("renoted by {author}"));
+            return post;
+        }
+
+        let mut body = String::new();
+        if let Some(cw) = self.cw {
+            body = format!("CW: {}\n\n", render_mfm(&cw));
+        }
+        body.push_str(&render_mfm(self.text.as_deref().unwrap_or_default()));
+
+        Post {
+            author,
+            body,
+            urls: self
+                .files
+                .into_iter()
+                .map(|f| labeled_url(f.comment.as_deref().unwrap_or_default(), f.url))
+                .collect(),
+            timestamp: Some(self.created_at),
+            score: Some(self.reactions.values().sum()),
+            edited: false,
+            labels: vec![],
+            quote: self.renote.map(|r| Quote::Post(Box::new(r.render(url)))),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct User {
+    description: Option<String>,
+    host: Option<String>,
+    id: String,
+    name: Option<String>,
+    username: String,
+}
+
+impl User {
+    fn name(&self, url: &Url) -> String {
+        // Local users have no host.
+        let host = self
+            .host
+            .as_deref()
+            .or_else(|| url.host_str())
+            .unwrap_or_default();
+        author_name(
+            &render_mfm(self.name.as_deref().unwrap_or_default()),
+            &format!("{}@{host}", self.username),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::Note;
+    use super::Path;
+    use super::render_mfm;
+    use crate::tests::parse_path_tests;
+
+    fn parse_note(json: &str) -> Note {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn renote() {
+        let url = Url::parse("https://example.com/notes/1").unwrap();
+        let post = parse_note(
+            r#"{
+                "id": "1",
+                "createdAt": "2024-01-02T03:04:05Z",
+                "user": {"id": "a", "username": "a", "host": null, "name": "A"},
+                "renote": {
+                    "id": "2",
+                    "createdAt": "2024-01-01T00:00:00Z",
+                    "user": {"id": "b", "username": "b", "host": "example.org", "name": null},
+                    "text": "Original",
+                    "reactions": {"👍": 2, "❤": 1}
+                }
+            }"#,
+        )
+        .render(&url);
+        assert_eq!(
+            post.to_string(),
+            "<@b@example.org> [2024-01-01 00:00 UTC, +3, renoted by A (@a@example.com)]\nOriginal"
+        );
+    }
+
+    #[test]
+    fn quote() {
+        let url = Url::parse("https://example.com/notes/1").unwrap();
+        let post = parse_note(
+            r#"{
+                "id": "1",
+                "createdAt": "2024-01-02T03:04:05Z",
+                "user": {"id": "a", "username": "a", "host": null, "name": "A"},
+                "text": "Look at this",
+                "renote": {
+                    "id": "2",
+                    "createdAt": "2024-01-01T00:00:00Z",
+                    "user": {"id": "b", "username": "b", "host": "example.org", "name": null},
+                    "text": "Original"
+                }
+            }"#,
+        )
+        .render(&url);
+        assert_eq!(post.body, "Look at this");
+        assert_eq!(
+            post.to_string(),
+            "<A (@a@example.com)> [2024-01-02 03:04 UTC, +0] Look at this\n\n\
+             > <@b@example.org> [2024-01-01 00:00 UTC, +0] Original"
+        );
+    }
+
+    #[test]
+    fn content_warning() {
+        let url = Url::parse("https://example.com/notes/1").unwrap();
+        let post = parse_note(
+            r#"{
+                "id": "1",
+                "createdAt": "2024-01-02T03:04:05Z",
+                "user": {"id": "a", "username": "a", "host": null, "name": ""},
+                "cw": "$[x2 Spoilers]",
+                "text": "The ending",
+                "files": [{"url": "https://example.com/a.png", "comment": "A scene"}]
+            }"#,
+        )
+        .render(&url);
+        assert_eq!(post.author, "@a@example.com");
+        assert_eq!(post.body, "CW: Spoilers\n\nThe ending");
+        assert_eq!(post.urls, ["A scene: https://example.com/a.png"]);
+    }
+
+    parse_path_tests!(
+        super::parse_path,
+        "https://example.com{}",
+        (
+            note,
+            "/notes/9abcdefghi",
+            Some(Path::Note {
+                note_id: "9abcdefghi"
+            })
+        ),
+        (
+            user,
+            "/@example",
+            Some(Path::User {
+                username: "example",
+                host: None
+            })
+        ),
+        (
+            remote_user,
+            "/@example@example.org",
+            Some(Path::User {
+                username: "example",
+                host: Some("example.org")
+            })
+        ),
+        (unknown, "/unknown", None),
+    );
+
+    #[test]
+    fn mfm() {
+        assert_eq!(render_mfm("plain **bold**"), "plain **bold**");
+        assert_eq!(render_mfm("$[spin.speed=2s hi] there"), "hi there");
+        assert_eq!(render_mfm("$[x2 $[tada nested]]!"), "nested!");
+        assert_eq!(
+            render_mfm("<small>quiet</small> <center>x</center>"),
+            "quiet x"
+        );
+        assert_eq!(render_mfm("love :blob_cat_heart:"), "love [blob cat heart]");
+        assert_eq!(render_mfm("at 12:30:45 [link]"), "at 12:30:45 [link]");
+        assert_eq!(render_mfm("$[unterminated"), "$[unterminated");
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use serde::Deserialize;
+use serde::Serialize;
+use ureq::Agent;
+use url::Url;
+
+use crate::cache;
+
+const CACHE: &str = "nodeinfo.json";
+const SCHEMA_PREFIX: &str = "http://nodeinfo.diaspora.software/ns/schema/";
+// Servers rarely change software, but a host without NodeInfo may be down or set it up later.
+const FOUND_TTL: Duration = Duration::from_hours(7 * 24);
+const NOT_FOUND_TTL: Duration = Duration::from_hours(24);
+
+/// Return the name of the server software at the host of `url` such as `mastodon` or `forgejo` as
+/// reported by the server's nodeinfo. The result is cached per host.
+pub(crate) fn software(agent: &Agent, url: &Url) -> Option<String> {
+    let host = url.host_str()?;
+    let now = SystemTime::now();
+    let mut hosts: HashMap<String, CachedSoftware> = cache::read(CACHE)
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default();
+
+    if let Some(cached) = hosts.get(host)
+        && !cached.is_expired(now)
+    {
+        return cached.software.clone();
+    }
+
+    let software = discover(agent, url);
+    hosts.insert(
+        host.to_owned(),
+        CachedSoftware {
+            software: software.clone(),
+            checked: now,
+        },
+    );
+    // Drop hosts which would be looked up again anyway so the cache doesn't grow forever.
+    hosts.retain(|_, cached| !cached.is_expired(now));
+    if let Ok(contents) = serde_json::to_string(&hosts) {
+        // Failing to cache only costs discovering the software again next time.
+        let _ = cache::write(CACHE, &contents);
+    }
+    software
+}
+
+fn discover(agent: &Agent, url: &Url) -> Option<String> {
+    let links: WellKnown = agent
+        .get(url.join("/.well-known/nodeinfo").ok()?.as_str())
+        .call()
+        .ok()?
+        .body_mut()
+        .read_json()
+        .ok()?;
+    let href = schema_link(links.links)?;
+
+    let nodeinfo: NodeInfo = agent
+        .get(url.join(&href).ok()?.as_str())
+        .call()
+        .ok()?
+        .body_mut()
+        .read_json()
+        .ok()?;
+    Some(nodeinfo.software.name.to_lowercase())
+}
+
+/// Return the link to the newest nodeinfo schema version among `links`.
+fn schema_link(links: Vec<Link>) -> Option<String> {
+    links
+        .into_iter()
+        .filter_map(|l| {
+            let version: Vec<u64> = l
+                .rel
+                .strip_prefix(SCHEMA_PREFIX)?
+                .split('.')
+                .map(str::parse)
+                .collect::<Result<_, _>>()
+                .ok()?;
+            Some((version, l.href))
+        })
+        .max_by(|a, b| a.0.cmp(&b.0))
+        .map(|(_, href)| href)
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct CachedSoftware {
+    software: Option<String>,
+    checked: SystemTime,
+}
+
+impl CachedSoftware {
+    fn is_expired(&self, now: SystemTime) -> bool {
+        let ttl = if self.software.is_some() {
+            FOUND_TTL
+        } else {
+            NOT_FOUND_TTL
+        };
+        // A time in the future means the clock changed so don't trust it.
+        now.duration_since(self.checked)
+            .map_or(true, |elapsed| elapsed >= ttl)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Link {
+    href: String,
+    rel: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct NodeInfo {
+    software: Software,
+}
+
+#[derive(Debug, Deserialize)]
+struct Software {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct WellKnown {
+    links: Vec<Link>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use std::time::SystemTime;
+
+    use super::CachedSoftware;
+    use super::Link;
+    use super::schema_link;
+
+    #[test]
+    fn expiry() {
+        let now = SystemTime::now();
+        let cached = |software: Option<&str>, hours| CachedSoftware {
+            software: software.map(ToOwned::to_owned),
+            checked: now - Duration::from_hours(hours),
+        };
+        assert!(!cached(Some("mastodon"), 48).is_expired(now));
+        assert!(cached(Some("mastodon"), 8 * 24).is_expired(now));
+        assert!(!cached(None, 1).is_expired(now));
+        assert!(cached(None, 48).is_expired(now));
+        assert!(
+            CachedSoftware {
+                software: None,
+                checked: now + Duration::from_hours(1),
+            }
+            .is_expired(now)
+        );
+    }
+
+    #[test]
+    fn newest_schema() {
+        let link = |rel: &str, href: &str| Link {
+            rel: rel.to_owned(),
+            href: href.to_owned(),
+        };
+        assert_eq!(
+            schema_link(vec![
+                link("http://nodeinfo.diaspora.software/ns/schema/2.0", "/2.0"),
+                link("https://example.com/other", "/other"),
+                link("http://nodeinfo.diaspora.software/ns/schema/2.1", "/2.1"),
+                link("http://nodeinfo.diaspora.software/ns/schema/1.10", "/1.10"),
+            ])
+            .as_deref(),
+            Some("/2.1")
+        );
+        assert_eq!(
+            schema_link(vec![link("https://example.com/other", "/other")]),
+            None
+        );
+    }
+}
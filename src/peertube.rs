@@ -0,0 +1,260 @@
+use anyhow::Context;
+use anyhow::bail;
+use scraper::Html;
+use serde::Deserialize;
+use ureq::Agent;
+use url::Url;
+
+use crate::Collection;
+use crate::Content;
+use crate::Item;
+use crate::html;
+
+#[derive(Debug, PartialEq)]
+enum Path<'a> {
+    Channel { handle: &'a str },
+    Playlist { playlist_id: &'a str },
+    Video { video_id: &'a str },
+}
+
+fn parse_path(url: &Url) -> Option<Path<'_>> {
+    let path_segments: Vec<_> = url
+        .path_segments()
+        .unwrap_or_else(|| "".split('/'))
+        .collect();
+
+    Some(match path_segments.as_slice() {
+        ["c" | "video-channels", handle, ..] => Path::Channel { handle },
+        ["w", "p", playlist_id] | ["videos", "watch", "playlist", playlist_id] => {
+            Path::Playlist { playlist_id }
+        }
+        ["w", video_id] | ["videos", "watch" | "embed", video_id] => Path::Video { video_id },
+        _ => return None,
+    })
+}
+
+/// Return whether `url` is a channel, playlist or video.
+pub(crate) fn supports_path(url: &Url) -> bool {
+    parse_path(url).is_some()
+}
+
+pub(crate) fn try_process(
+    agent: &Agent,
+    url: &Url,
+    tree: &Html,
+) -> Option<anyhow::Result<Content>> {
+    if html::select_single_element(tree, "meta[property=\"og:platform\"]")
+        .and_then(|e| e.attr("content"))
+        != Some("PeerTube")
+    {
+        return None;
+    }
+
+    process(agent, url)
+}
+
+pub(crate) fn process(agent: &Agent, url: &Url) -> Option<anyhow::Result<Content>> {
+    let path = parse_path(url)?;
+    let api_base = url.join("/api/v1/").expect("URL is valid");
+
+    Some((|| match path {
+        Path::Channel { handle } => {
+            let channel: Channel = agent
+                .get(api_base.join(&format!("video-channels/{handle}"))?.as_str())
+                .call()?
+                .body_mut()
+                .read_json()?;
+            let videos: List<Video> = agent
+                .get(
+                    api_base
+                        .join(&format!("video-channels/{handle}/videos"))?
+                        .as_str(),
+                )
+                .query("sort", "-publishedAt")
+                .query("count", "30")
+                .call()?
+                .body_mut()
+                .read_json()?;
+
+            Ok(Content::Collection(Collection {
+                title: Some(channel.display_name),
+                description: channel.description,
+                items: videos.data.into_iter().map(|v| v.item(url)).collect(),
+            }))
+        }
+
+        Path::Playlist { playlist_id } => {
+            let playlist: Playlist = agent
+                .get(
+                    api_base
+                        .join(&format!("video-playlists/{playlist_id}"))?
+                        .as_str(),
+                )
+                .call()?
+                .body_mut()
+                .read_json()?;
+            let elements: List<PlaylistElement> = agent
+                .get(
+                    api_base
+                        .join(&format!("video-playlists/{playlist_id}/videos"))?
+                        .as_str(),
+                )
+                .query("count", "100")
+                .call()?
+                .body_mut()
+                .read_json()?;
+
+            Ok(Content::Collection(Collection {
+                title: Some(playlist.display_name),
+                description: playlist.description,
+                items: elements
+                    .data
+                    .into_iter()
+                    // Deleted or private videos are null.
+                    .filter_map(|e| e.video)
+                    .map(|v| v.item(url))
+                    .collect(),
+            }))
+        }
+
+        Path::Video { video_id } => {
+            let video: VideoDetails = agent
+                .get(api_base.join(&format!("videos/{video_id}"))?.as_str())
+                .call()?
+                .body_mut()
+                .read_json()?;
+
+            // Prefer HLS so the player can pick the resolution.
+            let media_url = if let Some(playlist) = video.streaming_playlists.into_iter().next() {
+                playlist.playlist_url
+            } else if let Some(file) = video.files.into_iter().max_by_key(|f| f.resolution.id) {
+                file.file_url
+            } else {
+                bail!("PeerTube video has no files");
+            };
+            Ok(Content::Video(
+                Url::parse(&media_url).context("PeerTube returned invalid URL")?,
+            ))
+        }
+    })())
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Channel {
+    description: Option<String>,
+    display_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct File {
+    file_url: String,
+    resolution: Resolution,
+}
+
+#[derive(Debug, Deserialize)]
+struct List<T> {
+    data: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Playlist {
+    description: Option<String>,
+    display_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PlaylistElement {
+    video: Option<Video>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Resolution {
+    id: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StreamingPlaylist {
+    playlist_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Video {
+    description: Option<String>,
+    name: String,
+    short_uuid: String,
+}
+
+impl Video {
+    fn item(self, url: &Url) -> Item {
+        Item {
+            title: Some(self.name),
+            url: url
+                .join(&format!("/w/{}", self.short_uuid))
+                .expect("URL is valid")
+                .into(),
+            description: self.description,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VideoDetails {
+    #[serde(default)]
+    files: Vec<File>,
+    #[serde(default)]
+    streaming_playlists: Vec<StreamingPlaylist>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Path;
+    use crate::tests::parse_path_tests;
+
+    parse_path_tests!(
+        super::parse_path,
+        "https://example.com{}",
+        (
+            channel,
+            "/c/example_channel/videos",
+            Some(Path::Channel {
+                handle: "example_channel"
+            })
+        ),
+        (
+            video_channel,
+            "/video-channels/example_channel@example.org",
+            Some(Path::Channel {
+                handle: "example_channel@example.org"
+            })
+        ),
+        (
+            playlist,
+            "/w/p/abc123",
+            Some(Path::Playlist {
+                playlist_id: "abc123"
+            })
+        ),
+        (
+            old_playlist,
+            "/videos/watch/playlist/abc123",
+            Some(Path::Playlist {
+                playlist_id: "abc123"
+            })
+        ),
+        (video, "/w/abc123", Some(Path::Video { video_id: "abc123" })),
+        (
+            old_video,
+            "/videos/watch/9c9de5e8-0a1e-484a-b099-e80766180a6d",
+            Some(Path::Video {
+                video_id: "9c9de5e8-0a1e-484a-b099-e80766180a6d"
+            })
+        ),
+        (unknown, "/about", None),
+    );
+}
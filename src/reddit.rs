@@ -0,0 +1,300 @@
+use anyhow::Context;
+use anyhow::bail;
+use serde::Deserialize;
+use serde::Deserializer;
+use ureq::Agent;
+use url::Url;
+
+use crate::Content;
+use crate::Post;
+use crate::PostThread;
+use crate::Reply;
+use crate::TextType;
+use crate::Timestamp;
+use crate::process_generic;
+
+#[derive(Debug, PartialEq)]
+enum Path<'a> {
+    Comments,
+    ShortLink { post_id: &'a str },
+}
+
+fn parse_path(url: &Url) -> Option<Path<'_>> {
+    let path_segments: Vec<_> = url
+        .path_segments()
+        .unwrap_or_else(|| "".split('/'))
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    Some(if url.host_str() == Some("redd.it") {
+        let [post_id] = path_segments[..] else {
+            return None;
+        };
+        Path::ShortLink { post_id }
+    } else if (path_segments.len() >= 4
+        && matches!(path_segments[0], "r" | "u" | "user")
+        && path_segments[2] == "comments")
+        || (path_segments.len() >= 2 && path_segments[0] == "comments")
+    {
+        Path::Comments
+    } else {
+        return None;
+    })
+}
+
+pub(crate) fn process(agent: &Agent, url: &mut Url) -> Option<anyhow::Result<Content>> {
+    let path = parse_path(url)?;
+
+    match path {
+        Path::Comments => {
+            url.path_segments_mut()
+                .expect("cannot_be_a_base is checked earlier")
+                .pop_if_empty();
+            if !url.path().ends_with(".json") {
+                url.set_path(&(url.path().to_owned() + ".json"));
+            }
+        }
+        Path::ShortLink { post_id } => {
+            *url = Url::parse(&format!("https://www.reddit.com/comments/{post_id}.json"))
+                .expect("URL is valid");
+        }
+    }
+    url.query_pairs_mut().append_pair("raw_json", "1");
+
+    Some((|| {
+        let [post_listing, comment_listing]: [Thing; 2] =
+            agent.get(url.as_str()).call()?.body_mut().read_json()?;
+        let Thing::Listing(mut post_listing) = post_listing else {
+            bail!("Unexpected reddit response: {post_listing:?}");
+        };
+        let Some(Thing::Link(link)) = post_listing.children.pop() else {
+            bail!("Reddit response lacks a link");
+        };
+
+        if !link.is_self {
+            if link.domain == "v.redd.it" {
+                let playlist = link
+                    .media
+                    .and_then(|m| m.reddit_video)
+                    .map_or(link.url, |v| v.hls_url);
+                return Ok(Content::Video(
+                    Url::parse(&playlist).context("Reddit returned invalid URL")?,
+                ));
+            } else if link.domain == "i.redd.it" {
+                return process_generic(
+                    agent,
+                    &Url::parse(&link.url).context("Reddit returned invalid URL")?,
+                );
+            }
+        }
+
+        let mut comments = vec![];
+        if let Thing::Listing(listing) = comment_listing {
+            flatten_comments(listing, 0, &mut comments);
+        }
+
+        Ok(Content::Text(TextType::PostThread(PostThread {
+            title: Some(link.title),
+            before: vec![],
+            main: Post {
+                author: link.author,
+                body: link.selftext,
+                urls: if link.is_self { vec![] } else { vec![link.url] },
+                timestamp: Some(link.created_utc),
+                score: Some(link.score),
+                edited: link.edited,
+                labels: vec![],
+                quote: None,
+            },
+            after: comments,
+        })))
+    })())
+}
+
+fn flatten_comments(listing: Listing, depth: usize, replies: &mut Vec<Reply>) {
+    for child in listing.children {
+        if let Thing::Comment(comment) = child {
+            replies.push(Reply {
+                depth,
+                post: Post {
+                    author: comment.author,
+                    body: comment.body,
+                    urls: vec![],
+                    timestamp: Some(comment.created_utc),
+                    score: Some(comment.score),
+                    edited: comment.edited,
+                    labels: vec![],
+                    quote: None,
+                },
+            });
+            if let Replies::Thing(thing) = comment.replies
+                && let Thing::Listing(listing) = *thing
+            {
+                flatten_comments(listing, depth + 1, replies);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", content = "data")]
+enum Thing {
+    #[serde(rename = "t1")]
+    Comment(Comment),
+    #[serde(rename = "t3")]
+    Link(Link),
+    Listing(Listing),
+    #[serde(rename = "more")]
+    More(Ignore),
+}
+
+#[derive(Debug, Deserialize)]
+struct Ignore {}
+
+#[derive(Debug, Deserialize)]
+struct Comment {
+    author: String,
+    body: String,
+    created_utc: Timestamp,
+    #[serde(deserialize_with = "deserialize_edited")]
+    edited: bool,
+    replies: Replies,
+    score: i64,
+}
+
+// Reddit uses an empty string rather than null or an empty Listing for no replies.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Replies {
+    Thing(Box<Thing>),
+    #[expect(dead_code)]
+    Empty(String),
+}
+
+#[derive(Debug, Deserialize)]
+struct Link {
+    author: String,
+    created_utc: Timestamp,
+    domain: String,
+    #[serde(deserialize_with = "deserialize_edited")]
+    edited: bool,
+    is_self: bool,
+    media: Option<Media>,
+    score: i64,
+    selftext: String,
+    title: String,
+    url: String,
+}
+
+// Reddit uses false for unedited and the time of the edit otherwise.
+fn deserialize_edited<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    Ok(!matches!(
+        serde_json::Value::deserialize(deserializer)?,
+        serde_json::Value::Bool(false) | serde_json::Value::Null
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+struct Listing {
+    children: Vec<Thing>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Media {
+    reddit_video: Option<RedditVideo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RedditVideo {
+    hls_url: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Path;
+    use super::Thing;
+    use super::flatten_comments;
+    use crate::tests::parse_path_tests;
+
+    #[test]
+    fn nested_comments() {
+        let [post_listing, comment_listing]: [Thing; 2] = serde_json::from_str(
+            r#"[
+                {"kind": "Listing", "data": {"children": [{"kind": "t3", "data": {
+                    "author": "op", "created_utc": 1704067200.0, "domain": "self.example",
+                    "edited": false, "is_self": true, "media": null, "score": 10,
+                    "selftext": "Post", "title": "Title", "url": "https://www.reddit.com/"
+                }}]}},
+                {"kind": "Listing", "data": {"children": [
+                    {"kind": "t1", "data": {
+                        "author": "a", "body": "Top", "created_utc": 1704067260.0,
+                        "edited": 1704067300.0, "score": 5,
+                        "replies": {"kind": "Listing", "data": {"children": [
+                            {"kind": "t1", "data": {
+                                "author": "b", "body": "Nested", "created_utc": 1704067320.0,
+                                "edited": false, "score": 2, "replies": ""
+                            }},
+                            {"kind": "more", "data": {"count": 3, "children": ["x", "y"]}}
+                        ]}}
+                    }},
+                    {"kind": "t1", "data": {
+                        "author": "c", "body": "Second", "created_utc": 1704067380.0,
+                        "edited": false, "score": 1, "replies": ""
+                    }}
+                ]}}
+            ]"#,
+        )
+        .unwrap();
+        assert!(matches!(post_listing, Thing::Listing(_)));
+        let Thing::Listing(comment_listing) = comment_listing else {
+            panic!("Unexpected {comment_listing:?}");
+        };
+
+        let mut replies = vec![];
+        flatten_comments(comment_listing, 0, &mut replies);
+        let replies: Vec<_> = replies
+            .iter()
+            .map(|r| (r.depth, r.post.author.as_str(), r.post.edited))
+            .collect();
+        assert_eq!(replies, [(0, "a", true), (1, "b", false), (0, "c", false)]);
+    }
+
+    parse_path_tests!(
+        super::parse_path,
+        "https://www.reddit.com{}",
+        (
+            comments,
+            "/r/example/comments/1a2b3c/some_title/",
+            Some(Path::Comments)
+        ),
+        (comments_no_slug, "/comments/1a2b3c", Some(Path::Comments)),
+        (
+            comment_permalink,
+            "/r/example/comments/1a2b3c/some_title/d4e5f6/",
+            Some(Path::Comments)
+        ),
+        (
+            user_comments,
+            "/user/example/comments/1a2b3c/some_title/",
+            Some(Path::Comments)
+        ),
+        (subreddit, "/r/example/", None),
+        (unknown, "/unknown", None),
+    );
+
+    mod short_link {
+        use super::Path;
+        use crate::tests::parse_path_tests;
+
+        parse_path_tests!(
+            super::super::parse_path,
+            "https://redd.it{}",
+            (
+                short_link,
+                "/1a2b3c",
+                Some(Path::ShortLink { post_id: "1a2b3c" })
+            ),
+            (unknown, "/1a2b3c/extra", None),
+        );
+    }
+}
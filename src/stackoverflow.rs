@@ -1,491 +1,556 @@
+use std::collections::HashMap;
+use std::iter;
+use std::time::Duration;
+
 use anyhow::Context;
 use anyhow::bail;
 use serde::Deserialize;
+use serde::de::DeserializeOwned;
 use ureq::Agent;
 use url::Url;
 
+use crate::Collection;
+use crate::Config;
 use crate::Content;
+use crate::Item;
 use crate::Post;
 use crate::PostThread;
+use crate::Reply;
 use crate::TextType;
+use crate::Timestamp;
+use crate::cache;
 use crate::html;
 
-const API_BASE: &str = "https://api.stackexchange.com/2.3/";
-const FILTER: &str = "!T*hPNRA69ofM1izkPP";
-
-// curl --compressed 'https://api.stackexchange.com/2.3/sites?pagesize=10000'
-// jq -r '.items[] | "        " + ([.site_url] + .aliases | map(ltrimstr("https://") | "\"" + . + "\"") | join (" | ")) + " => Some(\"" + .api_site_parameter + "\"),"'
-#[expect(clippy::too_many_lines)]
-#[rustfmt::skip]
-fn site_tag(hostname: &str) -> Option<&'static str> {
-    match hostname {
-        "stackoverflow.com" | "www.stackoverflow.com" | "facebook.stackoverflow.com" => Some("stackoverflow"),
-        "serverfault.com" => Some("serverfault"),
-        "superuser.com" => Some("superuser"),
-        "meta.stackexchange.com" => Some("meta"),
-        "webapps.stackexchange.com" | "nothingtoinstall.com" => Some("webapps"),
-        "webapps.meta.stackexchange.com" | "meta.nothingtoinstall.com" | "meta.webapps.stackexchange.com" => Some("webapps.meta"),
-        "gaming.stackexchange.com" | "arqade.com" | "thearqade.com" => Some("gaming"),
-        "gaming.meta.stackexchange.com" | "meta.arqade.com" | "meta.thearqade.com" | "meta.gaming.stackexchange.com" => Some("gaming.meta"),
-        "webmasters.stackexchange.com" | "webmaster.stackexchange.com" => Some("webmasters"),
-        "webmasters.meta.stackexchange.com" | "meta.webmaster.stackexchange.com" | "meta.webmasters.stackexchange.com" => Some("webmasters.meta"),
-        "cooking.stackexchange.com" | "seasonedadvice.com" => Some("cooking"),
-        "cooking.meta.stackexchange.com" | "meta.seasonedadvice.com" | "meta.cooking.stackexchange.com" => Some("cooking.meta"),
-        "gamedev.stackexchange.com" => Some("gamedev"),
-        "gamedev.meta.stackexchange.com" | "meta.gamedev.stackexchange.com" => Some("gamedev.meta"),
-        "photo.stackexchange.com" | "photography.stackexchange.com" | "photos.stackexchange.com" => Some("photo"),
-        "photo.meta.stackexchange.com" | "meta.photography.stackexchange.com" | "meta.photos.stackexchange.com" | "meta.photo.stackexchange.com" => Some("photo.meta"),
-        "stats.stackexchange.com" | "statistics.stackexchange.com" | "crossvalidated.com" => Some("stats"),
-        "stats.meta.stackexchange.com" | "meta.statistics.stackexchange.com" | "meta.stats.stackexchange.com" => Some("stats.meta"),
-        "math.stackexchange.com" | "maths.stackexchange.com" | "mathematics.stackexchange.com" => Some("math"),
-        "math.meta.stackexchange.com" | "meta.math.stackexchange.com" => Some("math.meta"),
-        "diy.stackexchange.com" => Some("diy"),
-        "diy.meta.stackexchange.com" | "meta.diy.stackexchange.com" => Some("diy.meta"),
-        "meta.superuser.com" => Some("meta.superuser"),
-        "meta.serverfault.com" => Some("meta.serverfault"),
-        "gis.stackexchange.com" => Some("gis"),
-        "gis.meta.stackexchange.com" | "meta.gis.stackexchange.com" => Some("gis.meta"),
-        "tex.stackexchange.com" => Some("tex"),
-        "tex.meta.stackexchange.com" | "meta.tex.stackexchange.com" => Some("tex.meta"),
-        "askubuntu.com" | "ubuntu.stackexchange.com" => Some("askubuntu"),
-        "meta.askubuntu.com" | "meta.ubuntu.stackexchange.com" => Some("meta.askubuntu"),
-        "money.stackexchange.com" | "basicallymoney.com" | "www.basicallymoney.com" => Some("money"),
-        "money.meta.stackexchange.com" | "meta.money.stackexchange.com" => Some("money.meta"),
-        "english.stackexchange.com" | "elu.stackexchange.com" => Some("english"),
-        "english.meta.stackexchange.com" | "meta.english.stackexchange.com" => Some("english.meta"),
-        "stackapps.com" => Some("stackapps"),
-        "ux.stackexchange.com" | "ui.stackexchange.com" => Some("ux"),
-        "ux.meta.stackexchange.com" | "meta.ui.stackexchange.com" | "meta.ux.stackexchange.com" => Some("ux.meta"),
-        "unix.stackexchange.com" | "linux.stackexchange.com" => Some("unix"),
-        "unix.meta.stackexchange.com" | "meta.linux.stackexchange.com" | "meta.unix.stackexchange.com" => Some("unix.meta"),
-        "wordpress.stackexchange.com" => Some("wordpress"),
-        "wordpress.meta.stackexchange.com" | "meta.wordpress.stackexchange.com" => Some("wordpress.meta"),
-        "cstheory.stackexchange.com" => Some("cstheory"),
-        "cstheory.meta.stackexchange.com" | "meta.cstheory.stackexchange.com" => Some("cstheory.meta"),
-        "apple.stackexchange.com" | "askdifferent.com" => Some("apple"),
-        "apple.meta.stackexchange.com" | "meta.apple.stackexchange.com" => Some("apple.meta"),
-        "rpg.stackexchange.com" => Some("rpg"),
-        "rpg.meta.stackexchange.com" | "meta.rpg.stackexchange.com" => Some("rpg.meta"),
-        "bicycles.stackexchange.com" | "bicycle.stackexchange.com" | "cycling.stackexchange.com" | "bikes.stackexchange.com" => Some("bicycles"),
-        "bicycles.meta.stackexchange.com" | "meta.bicycle.stackexchange.com" | "meta.bicycles.stackexchange.com" => Some("bicycles.meta"),
-        "softwareengineering.stackexchange.com" | "programmer.stackexchange.com" | "programmers.stackexchange.com" | "swe.stackexchange.com" => Some("softwareengineering"),
-        "softwareengineering.meta.stackexchange.com" | "meta.programmers.stackexchange.com" | "meta.softwareengineering.stackexchange.com" | "meta.swe.stackexchange.com" => Some("softwareengineering.meta"),
-        "electronics.stackexchange.com" | "chiphacker.com" | "www.chiphacker.com" => Some("electronics"),
-        "electronics.meta.stackexchange.com" | "meta.electronics.stackexchange.com" => Some("electronics.meta"),
-        "android.stackexchange.com" => Some("android"),
-        "android.meta.stackexchange.com" | "meta.android.stackexchange.com" => Some("android.meta"),
-        "boardgames.stackexchange.com" | "boardgame.stackexchange.com" => Some("boardgames"),
-        "boardgames.meta.stackexchange.com" | "meta.boardgames.stackexchange.com" => Some("boardgames.meta"),
-        "physics.stackexchange.com" => Some("physics"),
-        "physics.meta.stackexchange.com" | "meta.physics.stackexchange.com" => Some("physics.meta"),
-        "homebrew.stackexchange.com" | "homebrewing.stackexchange.com" | "brewadvice.com" => Some("homebrew"),
-        "homebrew.meta.stackexchange.com" | "meta.homebrewing.stackexchange.com" | "meta.homebrew.stackexchange.com" => Some("homebrew.meta"),
-        "security.stackexchange.com" | "itsecurity.stackexchange.com" => Some("security"),
-        "security.meta.stackexchange.com" | "meta.itsecurity.stackexchange.com" | "meta.security.stackexchange.com" => Some("security.meta"),
-        "writing.stackexchange.com" | "writer.stackexchange.com" | "writers.stackexchange.com" => Some("writing"),
-        "writing.meta.stackexchange.com" | "meta.writing.stackexchange.com" | "meta.writers.stackexchange.com" | "writers.meta.stackexchange.com" => Some("writing.meta"),
-        "video.stackexchange.com" | "avp.stackexchange.com" => Some("video"),
-        "video.meta.stackexchange.com" | "meta.avp.stackexchange.com" | "meta.video.stackexchange.com" => Some("video.meta"),
-        "graphicdesign.stackexchange.com" | "graphicsdesign.stackexchange.com" | "graphicdesigns.stackexchange.com" => Some("graphicdesign"),
-        "graphicdesign.meta.stackexchange.com" | "meta.graphicdesign.stackexchange.com" => Some("graphicdesign.meta"),
-        "dba.stackexchange.com" => Some("dba"),
-        "dba.meta.stackexchange.com" | "meta.dba.stackexchange.com" => Some("dba.meta"),
-        "scifi.stackexchange.com" | "sciencefiction.stackexchange.com" | "fantasy.stackexchange.com" => Some("scifi"),
-        "scifi.meta.stackexchange.com" | "meta.scifi.stackexchange.com" => Some("scifi.meta"),
-        "codereview.stackexchange.com" => Some("codereview"),
-        "codereview.meta.stackexchange.com" | "meta.codereview.stackexchange.com" => Some("codereview.meta"),
-        "codegolf.stackexchange.com" => Some("codegolf"),
-        "codegolf.meta.stackexchange.com" | "meta.codegolf.stackexchange.com" => Some("codegolf.meta"),
-        "quant.stackexchange.com" => Some("quant"),
-        "quant.meta.stackexchange.com" | "meta.quant.stackexchange.com" => Some("quant.meta"),
-        "pm.stackexchange.com" => Some("pm"),
-        "pm.meta.stackexchange.com" | "meta.pm.stackexchange.com" => Some("pm.meta"),
-        "skeptics.stackexchange.com" | "skeptic.stackexchange.com" | "skepticexchange.com" => Some("skeptics"),
-        "skeptics.meta.stackexchange.com" | "meta.skeptics.stackexchange.com" => Some("skeptics.meta"),
-        "fitness.stackexchange.com" => Some("fitness"),
-        "fitness.meta.stackexchange.com" | "meta.fitness.stackexchange.com" => Some("fitness.meta"),
-        "drupal.stackexchange.com" => Some("drupal"),
-        "drupal.meta.stackexchange.com" | "meta.drupal.stackexchange.com" => Some("drupal.meta"),
-        "mechanics.stackexchange.com" | "garage.stackexchange.com" => Some("mechanics"),
-        "mechanics.meta.stackexchange.com" | "meta.garage.stackexchange.com" | "meta.mechanics.stackexchange.com" => Some("mechanics.meta"),
-        "parenting.stackexchange.com" => Some("parenting"),
-        "parenting.meta.stackexchange.com" | "meta.parenting.stackexchange.com" => Some("parenting.meta"),
-        "sharepoint.stackexchange.com" | "sharepointoverflow.com" | "www.sharepointoverflow.com" => Some("sharepoint"),
-        "sharepoint.meta.stackexchange.com" | "meta.sharepoint.stackexchange.com" => Some("sharepoint.meta"),
-        "music.stackexchange.com" | "guitars.stackexchange.com" | "guitar.stackexchange.com" => Some("music"),
-        "music.meta.stackexchange.com" | "meta.music.stackexchange.com" => Some("music.meta"),
-        "sqa.stackexchange.com" => Some("sqa"),
-        "sqa.meta.stackexchange.com" | "meta.sqa.stackexchange.com" => Some("sqa.meta"),
-        "judaism.stackexchange.com" | "mi.yodeya.com" | "yodeya.com" | "yodeya.stackexchange.com" | "miyodeya.com" => Some("judaism"),
-        "judaism.meta.stackexchange.com" | "meta.judaism.stackexchange.com" => Some("judaism.meta"),
-        "german.stackexchange.com" | "deutsch.stackexchange.com" => Some("german"),
-        "german.meta.stackexchange.com" | "meta.german.stackexchange.com" => Some("german.meta"),
-        "japanese.stackexchange.com" => Some("japanese"),
-        "japanese.meta.stackexchange.com" | "meta.japanese.stackexchange.com" => Some("japanese.meta"),
-        "philosophy.stackexchange.com" => Some("philosophy"),
-        "philosophy.meta.stackexchange.com" | "meta.philosophy.stackexchange.com" => Some("philosophy.meta"),
-        "gardening.stackexchange.com" | "landscaping.stackexchange.com" => Some("gardening"),
-        "gardening.meta.stackexchange.com" | "meta.gardening.stackexchange.com" => Some("gardening.meta"),
-        "travel.stackexchange.com" => Some("travel"),
-        "travel.meta.stackexchange.com" | "meta.travel.stackexchange.com" => Some("travel.meta"),
-        "crypto.stackexchange.com" | "cryptography.stackexchange.com" => Some("crypto"),
-        "crypto.meta.stackexchange.com" | "meta.cryptography.stackexchange.com" | "meta.crypto.stackexchange.com" => Some("crypto.meta"),
-        "dsp.stackexchange.com" | "signals.stackexchange.com" => Some("dsp"),
-        "dsp.meta.stackexchange.com" | "meta.dsp.stackexchange.com" => Some("dsp.meta"),
-        "french.stackexchange.com" => Some("french"),
-        "french.meta.stackexchange.com" | "meta.french.stackexchange.com" => Some("french.meta"),
-        "christianity.stackexchange.com" => Some("christianity"),
-        "christianity.meta.stackexchange.com" | "meta.christianity.stackexchange.com" => Some("christianity.meta"),
-        "bitcoin.stackexchange.com" => Some("bitcoin"),
-        "bitcoin.meta.stackexchange.com" | "meta.bitcoin.stackexchange.com" => Some("bitcoin.meta"),
-        "linguistics.stackexchange.com" | "linguist.stackexchange.com" => Some("linguistics"),
-        "linguistics.meta.stackexchange.com" | "meta.linguistics.stackexchange.com" => Some("linguistics.meta"),
-        "hermeneutics.stackexchange.com" => Some("hermeneutics"),
-        "hermeneutics.meta.stackexchange.com" | "meta.hermeneutics.stackexchange.com" => Some("hermeneutics.meta"),
-        "history.stackexchange.com" => Some("history"),
-        "history.meta.stackexchange.com" | "meta.history.stackexchange.com" => Some("history.meta"),
-        "bricks.stackexchange.com" => Some("bricks"),
-        "bricks.meta.stackexchange.com" | "meta.bricks.stackexchange.com" => Some("bricks.meta"),
-        "spanish.stackexchange.com" | "espanol.stackexchange.com" => Some("spanish"),
-        "spanish.meta.stackexchange.com" | "meta.spanish.stackexchange.com" => Some("spanish.meta"),
-        "scicomp.stackexchange.com" => Some("scicomp"),
-        "scicomp.meta.stackexchange.com" | "meta.scicomp.stackexchange.com" => Some("scicomp.meta"),
-        "movies.stackexchange.com" | "tv.stackexchange.com" => Some("movies"),
-        "movies.meta.stackexchange.com" | "meta.tv.stackexchange.com" | "meta.movies.stackexchange.com" => Some("movies.meta"),
-        "chinese.stackexchange.com" => Some("chinese"),
-        "chinese.meta.stackexchange.com" | "meta.chinese.stackexchange.com" => Some("chinese.meta"),
-        "biology.stackexchange.com" => Some("biology"),
-        "biology.meta.stackexchange.com" | "meta.biology.stackexchange.com" => Some("biology.meta"),
-        "poker.stackexchange.com" => Some("poker"),
-        "poker.meta.stackexchange.com" | "meta.poker.stackexchange.com" => Some("poker.meta"),
-        "mathematica.stackexchange.com" => Some("mathematica"),
-        "mathematica.meta.stackexchange.com" | "meta.mathematica.stackexchange.com" => Some("mathematica.meta"),
-        "psychology.stackexchange.com" | "cogsci.stackexchange.com" => Some("psychology"),
-        "psychology.meta.stackexchange.com" | "meta.cogsci.stackexchange.com" | "cogsci.meta.stackexchange.com" => Some("psychology.meta"),
-        "outdoors.stackexchange.com" => Some("outdoors"),
-        "outdoors.meta.stackexchange.com" | "meta.outdoors.stackexchange.com" => Some("outdoors.meta"),
-        "martialarts.stackexchange.com" => Some("martialarts"),
-        "martialarts.meta.stackexchange.com" | "meta.martialarts.stackexchange.com" => Some("martialarts.meta"),
-        "sports.stackexchange.com" => Some("sports"),
-        "sports.meta.stackexchange.com" | "meta.sports.stackexchange.com" => Some("sports.meta"),
-        "academia.stackexchange.com" | "academics.stackexchange.com" => Some("academia"),
-        "academia.meta.stackexchange.com" | "meta.academia.stackexchange.com" => Some("academia.meta"),
-        "cs.stackexchange.com" | "computerscience.stackexchange.com" => Some("cs"),
-        "cs.meta.stackexchange.com" | "meta.cs.stackexchange.com" => Some("cs.meta"),
-        "workplace.stackexchange.com" => Some("workplace"),
-        "workplace.meta.stackexchange.com" | "meta.workplace.stackexchange.com" => Some("workplace.meta"),
-        "chemistry.stackexchange.com" => Some("chemistry"),
-        "chemistry.meta.stackexchange.com" | "meta.chemistry.stackexchange.com" => Some("chemistry.meta"),
-        "chess.stackexchange.com" => Some("chess"),
-        "chess.meta.stackexchange.com" | "meta.chess.stackexchange.com" => Some("chess.meta"),
-        "raspberrypi.stackexchange.com" => Some("raspberrypi"),
-        "raspberrypi.meta.stackexchange.com" | "meta.raspberrypi.stackexchange.com" => Some("raspberrypi.meta"),
-        "russian.stackexchange.com" => Some("russian"),
-        "russian.meta.stackexchange.com" | "meta.russian.stackexchange.com" => Some("russian.meta"),
-        "islam.stackexchange.com" => Some("islam"),
-        "islam.meta.stackexchange.com" | "meta.islam.stackexchange.com" => Some("islam.meta"),
-        "salesforce.stackexchange.com" => Some("salesforce"),
-        "salesforce.meta.stackexchange.com" | "meta.salesforce.stackexchange.com" => Some("salesforce.meta"),
-        "patents.stackexchange.com" | "askpatents.com" | "askpatents.stackexchange.com" => Some("patents"),
-        "patents.meta.stackexchange.com" | "meta.askpatents.com" | "meta.askpatents.stackexchange.com" | "meta.patents.stackexchange.com" => Some("patents.meta"),
-        "genealogy.stackexchange.com" => Some("genealogy"),
-        "genealogy.meta.stackexchange.com" | "meta.genealogy.stackexchange.com" => Some("genealogy.meta"),
-        "robotics.stackexchange.com" => Some("robotics"),
-        "robotics.meta.stackexchange.com" | "meta.robotics.stackexchange.com" => Some("robotics.meta"),
-        "expressionengine.stackexchange.com" => Some("expressionengine"),
-        "expressionengine.meta.stackexchange.com" | "meta.expressionengine.stackexchange.com" => Some("expressionengine.meta"),
-        "politics.stackexchange.com" => Some("politics"),
-        "politics.meta.stackexchange.com" | "meta.politics.stackexchange.com" => Some("politics.meta"),
-        "anime.stackexchange.com" => Some("anime"),
-        "anime.meta.stackexchange.com" | "meta.anime.stackexchange.com" => Some("anime.meta"),
-        "magento.stackexchange.com" => Some("magento"),
-        "magento.meta.stackexchange.com" | "meta.magento.stackexchange.com" => Some("magento.meta"),
-        "ell.stackexchange.com" => Some("ell"),
-        "ell.meta.stackexchange.com" | "meta.ell.stackexchange.com" => Some("ell.meta"),
-        "sustainability.stackexchange.com" => Some("sustainability"),
-        "sustainability.meta.stackexchange.com" | "meta.sustainability.stackexchange.com" => Some("sustainability.meta"),
-        "tridion.stackexchange.com" => Some("tridion"),
-        "tridion.meta.stackexchange.com" | "meta.tridion.stackexchange.com" => Some("tridion.meta"),
-        "reverseengineering.stackexchange.com" => Some("reverseengineering"),
-        "reverseengineering.meta.stackexchange.com" | "meta.reverseengineering.stackexchange.com" => Some("reverseengineering.meta"),
-        "networkengineering.stackexchange.com" => Some("networkengineering"),
-        "networkengineering.meta.stackexchange.com" | "meta.networkengineering.stackexchange.com" => Some("networkengineering.meta"),
-        "opendata.stackexchange.com" => Some("opendata"),
-        "opendata.meta.stackexchange.com" | "meta.opendata.stackexchange.com" => Some("opendata.meta"),
-        "freelancing.stackexchange.com" => Some("freelancing"),
-        "freelancing.meta.stackexchange.com" | "meta.freelancing.stackexchange.com" => Some("freelancing.meta"),
-        "blender.stackexchange.com" => Some("blender"),
-        "blender.meta.stackexchange.com" | "meta.blender.stackexchange.com" => Some("blender.meta"),
-        "mathoverflow.net" | "mathoverflow.stackexchange.com" | "mathoverflow.com" => Some("mathoverflow.net"),
-        "meta.mathoverflow.net" => Some("meta.mathoverflow.net"),
-        "space.stackexchange.com" | "thefinalfrontier.stackexchange.com" => Some("space"),
-        "space.meta.stackexchange.com" | "meta.space.stackexchange.com" => Some("space.meta"),
-        "sound.stackexchange.com" | "sounddesign.stackexchange.com" => Some("sound"),
-        "sound.meta.stackexchange.com" | "meta.sound.stackexchange.com" => Some("sound.meta"),
-        "astronomy.stackexchange.com" => Some("astronomy"),
-        "astronomy.meta.stackexchange.com" | "meta.astronomy.stackexchange.com" => Some("astronomy.meta"),
-        "tor.stackexchange.com" => Some("tor"),
-        "tor.meta.stackexchange.com" | "meta.tor.stackexchange.com" => Some("tor.meta"),
-        "pets.stackexchange.com" => Some("pets"),
-        "pets.meta.stackexchange.com" | "meta.pets.stackexchange.com" => Some("pets.meta"),
-        "ham.stackexchange.com" => Some("ham"),
-        "ham.meta.stackexchange.com" | "meta.ham.stackexchange.com" => Some("ham.meta"),
-        "italian.stackexchange.com" => Some("italian"),
-        "italian.meta.stackexchange.com" | "meta.italian.stackexchange.com" => Some("italian.meta"),
-        "pt.stackoverflow.com" | "br.stackoverflow.com" | "stackoverflow.com.br" => Some("pt.stackoverflow"),
-        "pt.meta.stackoverflow.com" | "meta.br.stackoverflow.com" | "meta.pt.stackoverflow.com" => Some("pt.meta.stackoverflow"),
-        "aviation.stackexchange.com" => Some("aviation"),
-        "aviation.meta.stackexchange.com" | "meta.aviation.stackexchange.com" => Some("aviation.meta"),
-        "ebooks.stackexchange.com" => Some("ebooks"),
-        "ebooks.meta.stackexchange.com" | "meta.ebooks.stackexchange.com" => Some("ebooks.meta"),
-        "alcohol.stackexchange.com" | "beer.stackexchange.com" | "dranks.stackexchange.com" => Some("alcohol"),
-        "alcohol.meta.stackexchange.com" | "meta.beer.stackexchange.com" | "meta.alcohol.stackexchange.com" | "beer.meta.stackexchange.com" => Some("alcohol.meta"),
-        "softwarerecs.stackexchange.com" => Some("softwarerecs"),
-        "softwarerecs.meta.stackexchange.com" | "meta.softwarerecs.stackexchange.com" => Some("softwarerecs.meta"),
-        "arduino.stackexchange.com" => Some("arduino"),
-        "arduino.meta.stackexchange.com" | "meta.arduino.stackexchange.com" => Some("arduino.meta"),
-        "cs50.stackexchange.com" => Some("cs50"),
-        "cs50.meta.stackexchange.com" | "meta.cs50.stackexchange.com" => Some("cs50.meta"),
-        "expatriates.stackexchange.com" | "expats.stackexchange.com" => Some("expatriates"),
-        "expatriates.meta.stackexchange.com" | "meta.expatriates.stackexchange.com" => Some("expatriates.meta"),
-        "matheducators.stackexchange.com" => Some("matheducators"),
-        "matheducators.meta.stackexchange.com" | "meta.matheducators.stackexchange.com" => Some("matheducators.meta"),
-        "meta.stackoverflow.com" => Some("meta.stackoverflow"),
-        "earthscience.stackexchange.com" => Some("earthscience"),
-        "earthscience.meta.stackexchange.com" | "meta.earthscience.stackexchange.com" => Some("earthscience.meta"),
-        "joomla.stackexchange.com" => Some("joomla"),
-        "joomla.meta.stackexchange.com" | "meta.joomla.stackexchange.com" => Some("joomla.meta"),
-        "datascience.stackexchange.com" => Some("datascience"),
-        "datascience.meta.stackexchange.com" | "meta.datascience.stackexchange.com" => Some("datascience.meta"),
-        "puzzling.stackexchange.com" => Some("puzzling"),
-        "puzzling.meta.stackexchange.com" | "meta.puzzling.stackexchange.com" => Some("puzzling.meta"),
-        "craftcms.stackexchange.com" => Some("craftcms"),
-        "craftcms.meta.stackexchange.com" | "meta.craftcms.stackexchange.com" => Some("craftcms.meta"),
-        "buddhism.stackexchange.com" => Some("buddhism"),
-        "buddhism.meta.stackexchange.com" | "meta.buddhism.stackexchange.com" => Some("buddhism.meta"),
-        "hinduism.stackexchange.com" => Some("hinduism"),
-        "hinduism.meta.stackexchange.com" | "meta.hinduism.stackexchange.com" => Some("hinduism.meta"),
-        "communitybuilding.stackexchange.com" | "moderator.stackexchange.com" | "moderators.stackexchange.com" => Some("communitybuilding"),
-        "communitybuilding.meta.stackexchange.com" | "meta.moderators.stackexchange.com" | "meta.communitybuilding.stackexchange.com" => Some("communitybuilding.meta"),
-        "worldbuilding.stackexchange.com" => Some("worldbuilding"),
-        "worldbuilding.meta.stackexchange.com" | "meta.worldbuilding.stackexchange.com" => Some("worldbuilding.meta"),
-        "ja.stackoverflow.com" | "jp.stackoverflow.com" => Some("ja.stackoverflow"),
-        "ja.meta.stackoverflow.com" | "meta.ja.stackoverflow.com" => Some("ja.meta.stackoverflow"),
-        "emacs.stackexchange.com" => Some("emacs"),
-        "emacs.meta.stackexchange.com" | "meta.emacs.stackexchange.com" => Some("emacs.meta"),
-        "hsm.stackexchange.com" => Some("hsm"),
-        "hsm.meta.stackexchange.com" | "meta.hsm.stackexchange.com" => Some("hsm.meta"),
-        "economics.stackexchange.com" => Some("economics"),
-        "economics.meta.stackexchange.com" | "meta.economics.stackexchange.com" => Some("economics.meta"),
-        "lifehacks.stackexchange.com" => Some("lifehacks"),
-        "lifehacks.meta.stackexchange.com" | "meta.lifehacks.stackexchange.com" => Some("lifehacks.meta"),
-        "engineering.stackexchange.com" => Some("engineering"),
-        "engineering.meta.stackexchange.com" | "meta.engineering.stackexchange.com" => Some("engineering.meta"),
-        "coffee.stackexchange.com" => Some("coffee"),
-        "coffee.meta.stackexchange.com" | "meta.coffee.stackexchange.com" => Some("coffee.meta"),
-        "vi.stackexchange.com" | "vim.stackexchange.com" => Some("vi"),
-        "vi.meta.stackexchange.com" | "meta.vi.stackexchange.com" => Some("vi.meta"),
-        "musicfans.stackexchange.com" => Some("musicfans"),
-        "musicfans.meta.stackexchange.com" | "meta.musicfans.stackexchange.com" => Some("musicfans.meta"),
-        "woodworking.stackexchange.com" => Some("woodworking"),
-        "woodworking.meta.stackexchange.com" | "meta.woodworking.stackexchange.com" => Some("woodworking.meta"),
-        "civicrm.stackexchange.com" => Some("civicrm"),
-        "civicrm.meta.stackexchange.com" | "meta.civicrm.stackexchange.com" => Some("civicrm.meta"),
-        "medicalsciences.stackexchange.com" | "health.stackexchange.com" => Some("medicalsciences"),
-        "medicalsciences.meta.stackexchange.com" | "meta.health.stackexchange.com" | "health.meta.stackexchange.com" => Some("medicalsciences.meta"),
-        "ru.stackoverflow.com" | "hashcode.ru" | "stackoverflow.ru" => Some("ru.stackoverflow"),
-        "ru.meta.stackoverflow.com" | "meta.hashcode.ru" | "meta.ru.stackoverflow.com" => Some("ru.meta.stackoverflow"),
-        "rus.stackexchange.com" | "russ.hashcode.ru" | "russ.stackexchange.com" => Some("rus"),
-        "rus.meta.stackexchange.com" | "meta.rus.stackexchange.com" => Some("rus.meta"),
-        "mythology.stackexchange.com" => Some("mythology"),
-        "mythology.meta.stackexchange.com" | "meta.mythology.stackexchange.com" => Some("mythology.meta"),
-        "law.stackexchange.com" => Some("law"),
-        "law.meta.stackexchange.com" | "meta.law.stackexchange.com" => Some("law.meta"),
-        "opensource.stackexchange.com" => Some("opensource"),
-        "opensource.meta.stackexchange.com" | "meta.opensource.stackexchange.com" => Some("opensource.meta"),
-        "elementaryos.stackexchange.com" => Some("elementaryos"),
-        "elementaryos.meta.stackexchange.com" | "meta.elementaryos.stackexchange.com" => Some("elementaryos.meta"),
-        "portuguese.stackexchange.com" => Some("portuguese"),
-        "portuguese.meta.stackexchange.com" | "meta.portuguese.stackexchange.com" => Some("portuguese.meta"),
-        "computergraphics.stackexchange.com" => Some("computergraphics"),
-        "computergraphics.meta.stackexchange.com" | "meta.computergraphics.stackexchange.com" => Some("computergraphics.meta"),
-        "hardwarerecs.stackexchange.com" => Some("hardwarerecs"),
-        "hardwarerecs.meta.stackexchange.com" | "meta.hardwarerecs.stackexchange.com" => Some("hardwarerecs.meta"),
-        "es.stackoverflow.com" => Some("es.stackoverflow"),
-        "es.meta.stackoverflow.com" | "meta.es.stackoverflow.com" => Some("es.meta.stackoverflow"),
-        "3dprinting.stackexchange.com" | "threedprinting.stackexchange.com" => Some("3dprinting"),
-        "3dprinting.meta.stackexchange.com" | "meta.3dprinting.stackexchange.com" => Some("3dprinting.meta"),
-        "ethereum.stackexchange.com" => Some("ethereum"),
-        "ethereum.meta.stackexchange.com" | "meta.ethereum.stackexchange.com" => Some("ethereum.meta"),
-        "latin.stackexchange.com" => Some("latin"),
-        "latin.meta.stackexchange.com" | "meta.latin.stackexchange.com" => Some("latin.meta"),
-        "languagelearning.stackexchange.com" => Some("languagelearning"),
-        "languagelearning.meta.stackexchange.com" | "meta.languagelearning.stackexchange.com" => Some("languagelearning.meta"),
-        "retrocomputing.stackexchange.com" => Some("retrocomputing"),
-        "retrocomputing.meta.stackexchange.com" | "meta.retrocomputing.stackexchange.com" => Some("retrocomputing.meta"),
-        "crafts.stackexchange.com" => Some("crafts"),
-        "crafts.meta.stackexchange.com" | "meta.crafts.stackexchange.com" => Some("crafts.meta"),
-        "korean.stackexchange.com" => Some("korean"),
-        "korean.meta.stackexchange.com" | "meta.korean.stackexchange.com" => Some("korean.meta"),
-        "monero.stackexchange.com" => Some("monero"),
-        "monero.meta.stackexchange.com" | "meta.monero.stackexchange.com" => Some("monero.meta"),
-        "ai.stackexchange.com" => Some("ai"),
-        "ai.meta.stackexchange.com" | "meta.ai.stackexchange.com" => Some("ai.meta"),
-        "esperanto.stackexchange.com" => Some("esperanto"),
-        "esperanto.meta.stackexchange.com" | "meta.esperanto.stackexchange.com" => Some("esperanto.meta"),
-        "sitecore.stackexchange.com" => Some("sitecore"),
-        "sitecore.meta.stackexchange.com" | "meta.sitecore.stackexchange.com" => Some("sitecore.meta"),
-        "iot.stackexchange.com" => Some("iot"),
-        "iot.meta.stackexchange.com" | "meta.iot.stackexchange.com" => Some("iot.meta"),
-        "literature.stackexchange.com" => Some("literature"),
-        "literature.meta.stackexchange.com" | "meta.literature.stackexchange.com" => Some("literature.meta"),
-        "vegetarianism.stackexchange.com" | "vegetarian.stackexchange.com" | "veg.stackexchange.com" => Some("vegetarianism"),
-        "vegetarianism.meta.stackexchange.com" | "meta.vegetarian.stackexchange.com" | "meta.vegetarianism.stackexchange.com" | "veg.meta.stackexchange.com" => Some("vegetarianism.meta"),
-        "ukrainian.stackexchange.com" => Some("ukrainian"),
-        "ukrainian.meta.stackexchange.com" | "meta.ukrainian.stackexchange.com" => Some("ukrainian.meta"),
-        "devops.stackexchange.com" => Some("devops"),
-        "devops.meta.stackexchange.com" | "meta.devops.stackexchange.com" => Some("devops.meta"),
-        "bioinformatics.stackexchange.com" => Some("bioinformatics"),
-        "bioinformatics.meta.stackexchange.com" => Some("bioinformatics.meta"),
-        "cseducators.stackexchange.com" => Some("cseducators"),
-        "cseducators.meta.stackexchange.com" => Some("cseducators.meta"),
-        "interpersonal.stackexchange.com" | "interpersonalskills.stackexchange.com" | "ips.stackexchange.com" => Some("interpersonal"),
-        "interpersonal.meta.stackexchange.com" | "interpersonalskills.meta.stackexchange.com" | "ips.meta.stackexchange.com" => Some("interpersonal.meta"),
-        "iota.stackexchange.com" => Some("iota"),
-        "iota.meta.stackexchange.com" => Some("iota.meta"),
-        "stellar.stackexchange.com" => Some("stellar"),
-        "stellar.meta.stackexchange.com" => Some("stellar.meta"),
-        "conlang.stackexchange.com" => Some("conlang"),
-        "conlang.meta.stackexchange.com" => Some("conlang.meta"),
-        "quantumcomputing.stackexchange.com" => Some("quantumcomputing"),
-        "quantumcomputing.meta.stackexchange.com" => Some("quantumcomputing.meta"),
-        "eosio.stackexchange.com" => Some("eosio"),
-        "eosio.meta.stackexchange.com" => Some("eosio.meta"),
-        "tezos.stackexchange.com" => Some("tezos"),
-        "tezos.meta.stackexchange.com" => Some("tezos.meta"),
-        "or.stackexchange.com" => Some("or"),
-        "or.meta.stackexchange.com" => Some("or.meta"),
-        "drones.stackexchange.com" => Some("drones"),
-        "drones.meta.stackexchange.com" => Some("drones.meta"),
-        "mattermodeling.stackexchange.com" | "materials.stackexchange.com" => Some("mattermodeling"),
-        "mattermodeling.meta.stackexchange.com" | "materials.meta.stackexchange.com" => Some("mattermodeling.meta"),
-        "cardano.stackexchange.com" => Some("cardano"),
-        "cardano.meta.stackexchange.com" => Some("cardano.meta"),
-        "proofassistants.stackexchange.com" => Some("proofassistants"),
-        "proofassistants.meta.stackexchange.com" => Some("proofassistants.meta"),
-        "substrate.stackexchange.com" | "polkadot.stackexchange.com" => Some("substrate"),
-        "substrate.meta.stackexchange.com" | "polkadot.meta.stackexchange.com" => Some("substrate.meta"),
-        "bioacoustics.stackexchange.com" => Some("bioacoustics"),
-        "bioacoustics.meta.stackexchange.com" => Some("bioacoustics.meta"),
-        "solana.stackexchange.com" => Some("solana"),
-        "solana.meta.stackexchange.com" => Some("solana.meta"),
-        "langdev.stackexchange.com" | "languagedesign.stackexchange.com" => Some("langdev"),
-        "langdev.meta.stackexchange.com" | "languagedesign.meta.stackexchange.com" => Some("langdev.meta"),
-        "genai.stackexchange.com" => Some("genai"),
-        "genai.meta.stackexchange.com" => Some("genai.meta"),
-        _ => None,
+const API_BASE: &str = "https://api.stackexchange.com/2.3";
+// The built-in filter adding post bodies to the default fields.
+const FILTER: &str = "withbody";
+
+const SITES_CACHE: &str = "stackexchange-sites.json";
+const REFRESH_INTERVAL: Duration = Duration::from_hours(24);
+
+// Sites not under stackexchange.com, for use before the site list has been cached.
+const BUILTIN_SITES: &[(&str, &str)] = &[
+    ("askubuntu.com", "askubuntu"),
+    ("es.meta.stackoverflow.com", "es.meta.stackoverflow"),
+    ("es.stackoverflow.com", "es.stackoverflow"),
+    ("ja.meta.stackoverflow.com", "ja.meta.stackoverflow"),
+    ("ja.stackoverflow.com", "ja.stackoverflow"),
+    ("mathoverflow.net", "mathoverflow.net"),
+    ("meta.askubuntu.com", "meta.askubuntu"),
+    ("meta.mathoverflow.net", "meta.mathoverflow.net"),
+    ("meta.serverfault.com", "meta.serverfault"),
+    ("meta.stackoverflow.com", "meta.stackoverflow"),
+    ("meta.superuser.com", "meta.superuser"),
+    ("pt.meta.stackoverflow.com", "pt.meta.stackoverflow"),
+    ("pt.stackoverflow.com", "pt.stackoverflow"),
+    ("ru.meta.stackoverflow.com", "ru.meta.stackoverflow"),
+    ("ru.stackoverflow.com", "ru.stackoverflow"),
+    ("serverfault.com", "serverfault"),
+    ("stackapps.com", "stackapps"),
+    ("stackoverflow.com", "stackoverflow"),
+    ("superuser.com", "superuser"),
+    ("www.stackoverflow.com", "stackoverflow"),
+];
+
+/// A Stack Exchange API endpoint and the site to query on it.
+#[derive(Debug, PartialEq)]
+struct Site {
+    api_base: String,
+    name: String,
+}
+
+impl Site {
+    fn new(name: &str) -> Self {
+        Self {
+            api_base: API_BASE.to_owned(),
+            name: name.to_owned(),
+        }
     }
 }
 
-pub(crate) fn process(agent: &Agent, url: &Url) -> Option<anyhow::Result<Content>> {
-    let site_name = url.host_str().and_then(site_tag)?;
+/// Find the site served at `hostname`.
+///
+/// Hosts from the config take precedence, then the built-in list, then the cached copy of the
+/// `/sites` API response. A stackexchange.com host missing from all of those may be a new site so
+/// the cache is refreshed unless it already was recently, and failing that the subdomain is used
+/// as the site.
+fn find_site(agent: &Agent, config: &Config, hostname: &str) -> Option<Site> {
+    if let Some(site) = config.get_stackexchange_site(hostname) {
+        return Some(Site {
+            api_base: site
+                .api
+                .as_deref()
+                .unwrap_or(API_BASE)
+                .trim_end_matches('/')
+                .to_owned(),
+            name: site.site.clone(),
+        });
+    }
+
+    if let Some((_, name)) = BUILTIN_SITES.iter().find(|(host, _)| *host == hostname) {
+        return Some(Site::new(name));
+    }
+
+    if let Some(name) = cache::read(SITES_CACHE).and_then(|c| site_from_list(&c, hostname)) {
+        return Some(Site::new(&name));
+    }
+
+    let subdomain = hostname.strip_suffix(".stackexchange.com")?;
+    // Without a cache every visit would fetch the whole list again.
+    if cache::enabled()
+        && cache::age(SITES_CACHE).is_none_or(|age| age >= REFRESH_INTERVAL)
+        && let Some(name) = refresh_site_list(agent)
+            .ok()
+            .and_then(|sites| site_from_list(&sites, hostname))
+    {
+        return Some(Site::new(&name));
+    }
+    // The site parameter is the subdomain for all but a few renamed sites.
+    Some(Site::new(subdomain))
+}
+
+/// Fetch the `/sites` API response, cache it, and return it.
+fn refresh_site_list(agent: &Agent) -> anyhow::Result<String> {
+    let sites = agent
+        .get(format!("{API_BASE}/sites"))
+        .query("pagesize", "10000")
+        .call()?
+        .body_mut()
+        .read_to_string()?;
+    // Check the response before replacing a good cache with it.
+    serde_json::from_str::<Items<SiteInfo>>(&sites)?;
+    // Failing to cache only costs a later refresh.
+    let _ = cache::write(SITES_CACHE, &sites);
+    Ok(sites)
+}
+
+/// Return the `site` parameter for `hostname` from a `/sites` API response.
+fn site_from_list(sites: &str, hostname: &str) -> Option<String> {
+    let sites: Items<SiteInfo> = serde_json::from_str(sites).ok()?;
+    sites
+        .items
+        .into_iter()
+        .find(|s| {
+            iter::once(&s.site_url)
+                .chain(&s.aliases)
+                .any(|u| Url::parse(u).is_ok_and(|u| u.host_str() == Some(hostname)))
+        })
+        .map(|s| s.api_site_parameter)
+}
+
+#[derive(Debug, PartialEq)]
+enum Path<'a> {
+    Answer {
+        answer_id: u64,
+    },
+    Question {
+        question_id: u64,
+        answer_id: Option<u64>,
+    },
+    Tagged {
+        tags: &'a str,
+    },
+    User {
+        user_id: u64,
+    },
+}
+
+fn parse_path(url: &Url) -> Option<Path<'_>> {
+    let path_segments: Vec<_> = url
+        .path_segments()
+        .unwrap_or_else(|| "".split('/'))
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    Some(match path_segments[..] {
+        // Share links carry the ID of the sharing user.
+        ["a", answer_id] | ["a", answer_id, _] => Path::Answer {
+            answer_id: answer_id.parse().ok()?,
+        },
+        ["questions", "tagged", tags] => Path::Tagged { tags },
+        ["q" | "questions", question_id] | ["q" | "questions", question_id, _] => Path::Question {
+            question_id: question_id.parse().ok()?,
+            answer_id: None,
+        },
+        ["questions", question_id, _, answer_id] => Path::Question {
+            question_id: question_id.parse().ok()?,
+            answer_id: Some(answer_id.parse().ok()?),
+        },
+        ["users", user_id] | ["users", user_id, _] => Path::User {
+            user_id: user_id.parse().ok()?,
+        },
+        _ => return None,
+    })
+}
+
+pub(crate) fn process(
+    agent: &Agent,
+    config: &Config,
+    url: &Url,
+) -> Option<anyhow::Result<Content>> {
+    let path = parse_path(url)?;
+    let site = find_site(agent, config, url.host_str()?)?;
 
-    Some((|| {
-        let path_segments: Vec<_> = url
-            .path_segments()
-            .unwrap_or_else(|| "".split('/'))
-            .collect();
-        if path_segments.len() < 2 {
-            bail!("Unknown stackoverflow URL format");
+    Some((|| match path {
+        Path::Answer { answer_id } => {
+            let answer = get_answer(agent, &site, answer_id)?;
+            process_question(agent, url, &site, answer.question_id, Some(answer))
         }
 
-        if path_segments[0] == "a" {
-            let id = if path_segments[0] == "a" {
-                path_segments[1]
-            } else {
-                path_segments[3]
-            };
-            let mut answers: Items<Answer> = agent
-                .get(&format!(
-                    "{API_BASE}answers/{id}?site={site_name}&filter={FILTER}"
-                ))
-                .call()?
-                .body_mut()
-                .read_json()?;
-            let Some(answer) = answers.items.pop() else {
-                bail!("Unexpected answer response: {answers:?}");
-            };
-
-            Ok(Content::Text(TextType::Post(answer.render(url))))
-        } else if matches!(path_segments[0], "q" | "questions") {
-            let id = path_segments[1];
-            let mut questions: Items<Question> = agent
-                .get(&format!(
-                    "{API_BASE}questions/{id}?site={site_name}&filter={FILTER}"
-                ))
-                .call()?
-                .body_mut()
-                .read_json()?;
-            let Some(question) = questions.items.pop() else {
-                bail!("Unexpected question response: {questions:?}");
-            };
-
-            let question_post = Post {
-                author: question.owner.display_name,
-                body: html::render(&question.body, url),
-                urls: vec![],
-            };
-
-            Ok(Content::Text(TextType::PostThread(
-                if let Some(answer_id) = path_segments.get(3).and_then(|s| s.parse::<u64>().ok()) {
-                    let answer = question
-                        .answers
-                        .and_then(|a| a.into_iter().find(|a| a.answer_id == answer_id))
-                        .context("question {id} missing requested answer id {answer_id}")?;
-                    PostThread {
-                        title: Some(question.title),
-                        before: vec![question_post],
-                        main: answer.render(url),
-                        after: vec![],
-                    }
-                } else {
-                    PostThread {
-                        title: Some(question.title),
-                        before: vec![],
-                        main: question_post,
-                        after: question
-                            .answers
-                            .unwrap_or_else(Vec::new)
-                            .into_iter()
-                            .map(|a| a.render(url))
-                            .collect(),
-                    }
-                },
-            )))
-        } else {
-            bail!("Unknown stackoverflow URL format");
+        Path::Question {
+            question_id,
+            answer_id,
+        } => {
+            let answer = answer_id
+                .map(|answer_id| get_answer(agent, &site, answer_id))
+                .transpose()?;
+            if let Some(answer) = &answer
+                && answer.question_id != question_id
+            {
+                bail!(
+                    "Answer {} does not belong to question {question_id}",
+                    answer.answer_id
+                );
+            }
+            process_question(agent, url, &site, question_id, answer)
         }
+
+        Path::Tagged { tags } => process_tagged(agent, url, &site, tags),
+
+        Path::User { user_id } => process_user(agent, url, &site, user_id),
     })())
 }
 
+fn get_answer(agent: &Agent, site: &Site, id: u64) -> anyhow::Result<Answer> {
+    let mut answers: Vec<Answer> = request(agent, &format!("answers/{id}"), site, &[])?;
+    answers
+        .pop()
+        .with_context(|| format!("Answer {id} not found"))
+}
+
+/// Show the question `id` followed by `answer` if given, marked as the linked answer, and
+/// otherwise all of the question's answers.
+fn process_question(
+    agent: &Agent,
+    url: &Url,
+    site: &Site,
+    id: u64,
+    answer: Option<Answer>,
+) -> anyhow::Result<Content> {
+    let mut questions: Vec<Question> = request(agent, &format!("questions/{id}"), site, &[])?;
+    let Some(question) = questions.pop() else {
+        bail!("Unexpected question response: {questions:?}");
+    };
+
+    let title = if question.tags.is_empty() {
+        question.title
+    } else {
+        format!("{}\nTags: {}", question.title, question.tags.join(", "))
+    };
+    let question_post = Post {
+        author: html::render(&question.owner.display_name, url),
+        body: html::render(&question.body, url),
+        urls: vec![],
+        timestamp: Some(question.creation_date),
+        score: Some(question.score),
+        edited: question.last_edit_date.is_some(),
+        labels: vec![],
+        quote: None,
+    };
+
+    Ok(Content::Text(TextType::PostThread(
+        if let Some(answer) = answer {
+            let mut comments = get_comments(agent, site, &[answer.answer_id])?;
+            let after = render_comments(comments.remove(&answer.answer_id), url);
+            // The question comes first for context, so mark which post the URL was for.
+            let mut main = answer.render(url);
+            main.labels.push("linked answer".to_owned());
+            PostThread {
+                title: Some(title),
+                before: vec![question_post],
+                main,
+                after,
+            }
+        } else {
+            let mut answers: Vec<Answer> = request_all(
+                agent,
+                &format!("questions/{id}/answers"),
+                site,
+                &[("sort", "votes"), ("order", "desc")],
+            )?;
+            // Stable so the remaining answers stay ordered by score.
+            answers.sort_by_key(|a| !a.is_accepted);
+
+            let post_ids: Vec<_> = iter::once(question.question_id)
+                .chain(answers.iter().map(|a| a.answer_id))
+                .collect();
+            let mut comments = get_comments(agent, site, &post_ids)?;
+
+            let mut after = render_comments(comments.remove(&question.question_id), url);
+            for answer in answers {
+                let answer_comments = comments.remove(&answer.answer_id);
+                after.push(answer.render(url).into());
+                after.extend(render_comments(answer_comments, url));
+            }
+
+            PostThread {
+                title: Some(title),
+                before: vec![],
+                main: question_post,
+                after,
+            }
+        },
+    )))
+}
+
+/// Show the most recent questions with all of `tags`, which are separated by `+` as on the site.
+fn process_tagged(agent: &Agent, url: &Url, site: &Site, tags: &str) -> anyhow::Result<Content> {
+    let tags = tags
+        .split('+')
+        .map(|t| percent_encoding::percent_decode_str(t).decode_utf8())
+        .collect::<Result<Vec<_>, _>>()?
+        .join(";");
+    let questions: Vec<Question> = request(
+        agent,
+        "questions",
+        site,
+        &[
+            ("tagged", &tags),
+            ("sort", "creation"),
+            ("order", "desc"),
+            ("pagesize", "30"),
+        ],
+    )?;
+
+    Ok(Content::Collection(Collection {
+        title: Some(format!("Questions tagged {}", tags.replace(';', ", "))),
+        description: None,
+        items: questions
+            .into_iter()
+            .map(|q| Item {
+                title: Some(html::render(&q.title, url)),
+                url: q.link,
+                description: Some(format!(
+                    "Score: {}, Answers: {}, Tags: {}",
+                    q.score,
+                    q.answer_count,
+                    q.tags.join(", ")
+                )),
+            })
+            .collect(),
+    }))
+}
+
+/// Show the profile of user `id` followed by their top answers.
+fn process_user(agent: &Agent, url: &Url, site: &Site, id: u64) -> anyhow::Result<Content> {
+    let mut users: Vec<UserProfile> = request(agent, &format!("users/{id}"), site, &[])?;
+    let Some(user) = users.pop() else {
+        bail!("User {id} not found");
+    };
+
+    let answers: Vec<Answer> = request(
+        agent,
+        &format!("users/{id}/answers"),
+        site,
+        &[("sort", "votes"), ("order", "desc"), ("pagesize", "10")],
+    )?;
+    // The answer fields don't include the question title.
+    let question_ids = answers
+        .iter()
+        .map(|a| a.question_id.to_string())
+        .collect::<Vec<_>>()
+        .join(";");
+    let titles: HashMap<u64, String> = if answers.is_empty() {
+        HashMap::new()
+    } else {
+        request::<Question>(agent, &format!("questions/{question_ids}"), site, &[])?
+            .into_iter()
+            .map(|q| (q.question_id, html::render(&q.title, url)))
+            .collect()
+    };
+
+    let mut body = format!("Reputation: {}", user.reputation);
+    if let Some(location) = user.location {
+        body.push_str("\nLocation: ");
+        body.push_str(&html::render(&location, url));
+    }
+
+    Ok(Content::Text(TextType::PostThread(PostThread {
+        title: None,
+        before: vec![],
+        main: Post {
+            author: html::render(&user.display_name, url),
+            body,
+            urls: user
+                .website_url
+                .into_iter()
+                .filter(|u| !u.is_empty())
+                .collect(),
+            timestamp: Some(user.creation_date),
+            ..Post::default()
+        },
+        after: answers
+            .into_iter()
+            .map(|answer| {
+                let question_id = answer.question_id;
+                let mut post = answer.render(url);
+                if let Some(title) = titles.get(&question_id) {
+                    post.body = format!("Re: {title}\n\n{}", post.body);
+                }
+                post.into()
+            })
+            .collect(),
+    })))
+}
+
+fn get_comments(
+    agent: &Agent,
+    site: &Site,
+    post_ids: &[u64],
+) -> anyhow::Result<HashMap<u64, Vec<Comment>>> {
+    let mut comments: HashMap<u64, Vec<Comment>> = HashMap::new();
+    // The API accepts at most 100 IDs per request.
+    for chunk in post_ids.chunks(100) {
+        let ids = chunk
+            .iter()
+            .map(u64::to_string)
+            .collect::<Vec<_>>()
+            .join(";");
+        let chunk_comments: Vec<Comment> = request_all(
+            agent,
+            &format!("posts/{ids}/comments"),
+            site,
+            &[("sort", "creation"), ("order", "asc")],
+        )?;
+        for comment in chunk_comments {
+            comments.entry(comment.post_id).or_default().push(comment);
+        }
+    }
+    Ok(comments)
+}
+
+fn render_comments(comments: Option<Vec<Comment>>, url: &Url) -> Vec<Reply> {
+    comments
+        .unwrap_or_default()
+        .into_iter()
+        .map(|c| Reply {
+            depth: 1,
+            post: Post {
+                author: html::render(&c.owner.display_name, url),
+                body: html::render(&c.body, url),
+                urls: vec![],
+                timestamp: Some(c.creation_date),
+                score: Some(c.score),
+                edited: c.edited,
+                labels: vec![],
+                quote: None,
+            },
+        })
+        .collect()
+}
+
+fn request<T: DeserializeOwned>(
+    agent: &Agent,
+    path: &str,
+    site: &Site,
+    query: &[(&str, &str)],
+) -> anyhow::Result<Vec<T>> {
+    Ok(request_page(agent, path, site, query, 1)?.items)
+}
+
+/// Like `request` but follow `has_more` to return the items of every page.
+fn request_all<T: DeserializeOwned>(
+    agent: &Agent,
+    path: &str,
+    site: &Site,
+    query: &[(&str, &str)],
+) -> anyhow::Result<Vec<T>> {
+    // Each page costs API quota, so give up on pathologically long results.
+    const MAX_PAGES: u64 = 25;
+
+    let mut items = vec![];
+    for page in 1..=MAX_PAGES {
+        let response: Items<T> = request_page(agent, path, site, query, page)?;
+        items.extend(response.items);
+        if !response.has_more {
+            break;
+        }
+    }
+    Ok(items)
+}
+
+fn request_page<T: DeserializeOwned>(
+    agent: &Agent,
+    path: &str,
+    site: &Site,
+    query: &[(&str, &str)],
+    page: u64,
+) -> anyhow::Result<Items<T>> {
+    let pagesize = if query.iter().any(|(k, _)| *k == "pagesize") {
+        None
+    } else {
+        Some(("pagesize", "100"))
+    };
+    Ok(agent
+        .get(format!("{}/{path}", site.api_base))
+        .query_pairs([("site", site.name.as_str()), ("filter", FILTER)])
+        .query_pairs(query.iter().copied().chain(pagesize))
+        .query("page", page.to_string())
+        .call()?
+        .body_mut()
+        .read_json()?)
+}
+
 #[derive(Debug, Deserialize)]
 struct Items<T> {
+    #[serde(default)]
+    has_more: bool,
     items: Vec<T>,
 }
 
+#[derive(Debug, Deserialize)]
+struct SiteInfo {
+    #[serde(default)]
+    aliases: Vec<String>,
+    api_site_parameter: String,
+    site_url: String,
+}
+
 #[derive(Debug, Deserialize)]
 struct Answer {
     #[expect(clippy::struct_field_names)]
     answer_id: u64,
     body: String,
+    creation_date: Timestamp,
+    is_accepted: bool,
+    last_edit_date: Option<Timestamp>,
     owner: User,
+    question_id: u64,
+    score: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Comment {
+    body: String,
+    creation_date: Timestamp,
+    edited: bool,
+    owner: User,
+    post_id: u64,
+    score: i64,
 }
 
 #[derive(Debug, Deserialize)]
 struct Question {
-    answers: Option<Vec<Answer>>,
+    answer_count: u64,
     body: String,
+    creation_date: Timestamp,
+    last_edit_date: Option<Timestamp>,
+    link: String,
     owner: User,
+    #[expect(clippy::struct_field_names)]
+    question_id: u64,
+    score: i64,
+    tags: Vec<String>,
     title: String,
 }
 
@@ -494,12 +559,122 @@ struct User {
     display_name: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct UserProfile {
+    creation_date: Timestamp,
+    display_name: String,
+    location: Option<String>,
+    reputation: i64,
+    website_url: Option<String>,
+}
+
 impl Answer {
     fn render(self, url: &Url) -> Post {
         Post {
             author: html::render(&self.owner.display_name, url),
             body: html::render(&self.body, url),
             urls: vec![],
+            timestamp: Some(self.creation_date),
+            score: Some(self.score),
+            edited: self.last_edit_date.is_some(),
+            labels: if self.is_accepted {
+                vec!["accepted".to_owned()]
+            } else {
+                vec![]
+            },
+            quote: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Path;
+    use super::site_from_list;
+    use crate::tests::parse_path_tests;
+
+    parse_path_tests!(
+        super::parse_path,
+        "https://stackoverflow.com{}",
+        (answer, "/a/123", Some(Path::Answer { answer_id: 123 })),
+        (
+            answer_share,
+            "/a/123/456",
+            Some(Path::Answer { answer_id: 123 })
+        ),
+        (
+            question_short,
+            "/q/123",
+            Some(Path::Question {
+                question_id: 123,
+                answer_id: None
+            })
+        ),
+        (
+            question_share,
+            "/q/123/456",
+            Some(Path::Question {
+                question_id: 123,
+                answer_id: None
+            })
+        ),
+        (
+            question,
+            "/questions/123/some-title",
+            Some(Path::Question {
+                question_id: 123,
+                answer_id: None
+            })
+        ),
+        (
+            question_answer,
+            "/questions/123/some-title/789",
+            Some(Path::Question {
+                question_id: 123,
+                answer_id: Some(789)
+            })
+        ),
+        (
+            tagged,
+            "/questions/tagged/rust+c%2b%2b",
+            Some(Path::Tagged {
+                tags: "rust+c%2b%2b"
+            })
+        ),
+        (user, "/users/42", Some(Path::User { user_id: 42 })),
+        (
+            user_slug,
+            "/users/42/someone",
+            Some(Path::User { user_id: 42 })
+        ),
+        (questions, "/questions", None),
+        (invalid_id, "/q/abc", None),
+        (unknown, "/tags", None),
+    );
+
+    #[test]
+    fn site_list_lookup() {
+        let sites = r#"{"items": [
+            {"site_url": "https://stackoverflow.com", "api_site_parameter": "stackoverflow"},
+            {
+                "site_url": "https://gaming.stackexchange.com",
+                "aliases": ["https://arqade.com", "https://thearqade.com"],
+                "api_site_parameter": "gaming"
+            }
+        ]}"#;
+        assert_eq!(
+            site_from_list(sites, "stackoverflow.com").as_deref(),
+            Some("stackoverflow")
+        );
+        assert_eq!(
+            site_from_list(sites, "gaming.stackexchange.com").as_deref(),
+            Some("gaming")
+        );
+        assert_eq!(
+            site_from_list(sites, "arqade.com").as_deref(),
+            Some("gaming")
+        );
+        assert_eq!(site_from_list(sites, "example.com"), None);
+        assert_eq!(site_from_list("not json", "stackoverflow.com"), None);
+    }
+}
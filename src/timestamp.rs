@@ -0,0 +1,203 @@
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::de;
+use serde::de::Visitor;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// A point in time as seconds since the Unix epoch.
+///
+/// APIs disagree on how to represent time, so this deserializes from either a number of seconds
+/// since the epoch or an RFC 3339 string.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub(crate) struct Timestamp(i64);
+
+impl Timestamp {
+    /// Parse an RFC 3339 timestamp such as `2011-03-04T05:06:07.89-05:00`.
+    fn parse_rfc3339(s: &str) -> Option<Self> {
+        let number = |range: std::ops::Range<usize>| -> Option<i64> {
+            let digits = s.get(range)?;
+            if digits.bytes().all(|b| b.is_ascii_digit()) {
+                digits.parse().ok()
+            } else {
+                None
+            }
+        };
+
+        if s.len() < 19
+            || s.as_bytes()[4] != b'-'
+            || s.as_bytes()[7] != b'-'
+            || !matches!(s.as_bytes()[10], b'T' | b't' | b' ')
+            || s.as_bytes()[13] != b':'
+            || s.as_bytes()[16] != b':'
+        {
+            return None;
+        }
+        let year = number(0..4)?;
+        let month = number(5..7)?;
+        let day = number(8..10)?;
+        let hour = number(11..13)?;
+        let minute = number(14..16)?;
+        let second = number(17..19)?;
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 {
+            return None;
+        }
+
+        let mut rest = &s[19..];
+        if let Some(fraction) = rest.strip_prefix('.') {
+            rest = fraction.trim_start_matches(|c: char| c.is_ascii_digit());
+        }
+        let offset = match rest {
+            "Z" | "z" | "" => 0,
+            _ => {
+                let sign = match rest.as_bytes()[0] {
+                    b'+' => 1,
+                    b'-' => -1,
+                    _ => return None,
+                };
+                let (hours, minutes) = rest[1..].split_once(':')?;
+                if hours.len() != 2 || minutes.len() != 2 {
+                    return None;
+                }
+                sign * (hours.parse::<i64>().ok()? * 60 + minutes.parse::<i64>().ok()?) * 60
+            }
+        };
+
+        Some(Self(
+            days_from_civil(year, month, day) * SECONDS_PER_DAY
+                + hour * 60 * 60
+                + minute * 60
+                + second
+                - offset,
+        ))
+    }
+}
+
+impl Display for Timestamp {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        let (year, month, day) = civil_from_days(self.0.div_euclid(SECONDS_PER_DAY));
+        let seconds = self.0.rem_euclid(SECONDS_PER_DAY);
+        write!(
+            f,
+            "{year:04}-{month:02}-{day:02} {:02}:{:02} UTC",
+            seconds / 3600,
+            seconds % 3600 / 60
+        )
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TimestampVisitor;
+
+        impl Visitor<'_> for TimestampVisitor {
+            type Value = Timestamp;
+
+            fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+                f.write_str("a Unix timestamp or an RFC 3339 string")
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                Ok(Timestamp(v))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                Ok(Timestamp(
+                    i64::try_from(v).map_err(|_| E::custom("timestamp out of range"))?,
+                ))
+            }
+
+            #[expect(clippy::cast_possible_truncation)]
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+                Ok(Timestamp(v as i64))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                Timestamp::parse_rfc3339(v)
+                    .ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))
+            }
+        }
+
+        deserializer.deserialize_any(TimestampVisitor)
+    }
+}
+
+// Algorithms from https://howardhinnant.github.io/date_algorithms.html
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Timestamp;
+
+    macro_rules! parse_tests {
+        ($(($name: ident, $input: expr, $expected: expr),)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    assert_eq!(Timestamp::parse_rfc3339($input), $expected.map(Timestamp));
+                }
+            )*
+        }
+    }
+
+    parse_tests!(
+        (epoch, "1970-01-01T00:00:00Z", Some(0)),
+        (utc, "2011-03-04T05:06:07Z", Some(1_299_215_167)),
+        (fraction, "2011-03-04T05:06:07.891Z", Some(1_299_215_167)),
+        (offset, "2011-03-04T00:06:07-05:00", Some(1_299_215_167)),
+        (no_zone, "2011-03-04T05:06:07", Some(1_299_215_167)),
+        (leap_day, "2024-02-29T00:00:00Z", Some(1_709_164_800)),
+        (invalid_month, "2011-13-04T05:06:07Z", None),
+        (invalid_zone, "2011-03-04T05:06:07EST", None),
+        (too_short, "2011-03-04", None),
+    );
+
+    #[test]
+    fn display() {
+        assert_eq!(Timestamp(1_299_215_167).to_string(), "2011-03-04 05:06 UTC");
+        assert_eq!(Timestamp(1_709_164_800).to_string(), "2024-02-29 00:00 UTC");
+        assert_eq!(Timestamp(-1).to_string(), "1969-12-31 23:59 UTC");
+    }
+
+    #[test]
+    fn deserialize() {
+        assert_eq!(
+            serde_json::from_str::<Timestamp>("1299215167").unwrap(),
+            Timestamp(1_299_215_167)
+        );
+        assert_eq!(
+            serde_json::from_str::<Timestamp>("1299215167.0").unwrap(),
+            Timestamp(1_299_215_167)
+        );
+        assert_eq!(
+            serde_json::from_str::<Timestamp>("\"2011-03-04T05:06:07Z\"").unwrap(),
+            Timestamp(1_299_215_167)
+        );
+        assert!(serde_json::from_str::<Timestamp>("\"yesterday\"").is_err());
+    }
+}
@@ -1,7 +1,11 @@
 use std::collections::HashMap;
+use std::num::NonZeroUsize;
 
+use anyhow::Context;
 use anyhow::bail;
+use scraper::Html;
 use serde::Deserialize;
+use serde::de::DeserializeOwned;
 use ureq::Agent;
 use url::Url;
 
@@ -9,73 +13,530 @@ use crate::Article;
 use crate::Content;
 use crate::LINE_LENGTH;
 use crate::TextType;
+use crate::html;
+use crate::process_generic;
+
+// Elements that are navigation or editing aids rather than article content.
+const CLUTTER_SELECTOR: &str = ".mw-editsection, .navbox, .vertical-navbox, .navbox-styles, \
+    .sistersitebox, .reference, .mw-references-wrap, .reflist, .metadata, .noprint, \
+    .mw-empty-elt, .mw-jump-link";
+
+// Domains of Wikimedia projects. Each serves every language and the mobile site as a subdomain.
+const PROJECT_DOMAINS: &[&str] = &[
+    "mediawiki.org",
+    "wikibooks.org",
+    "wikidata.org",
+    "wikinews.org",
+    "wikipedia.org",
+    "wikiquote.org",
+    "wikisource.org",
+    "wikiversity.org",
+    "wikivoyage.org",
+    "wiktionary.org",
+];
+
+// Wikis under wikimedia.org, which also hosts non-wiki services such as Phabricator.
+const WIKIMEDIA_ORG_WIKIS: &[&str] = &[
+    "commons",
+    "foundation",
+    "incubator",
+    "meta",
+    "outreach",
+    "species",
+    "wikitech",
+];
+
+const FILE_NAMESPACE: i64 = 6;
+// Names every wiki accepts for the file namespace besides any localized ones.
+const FILE_NAMESPACE_NAMES: &[&str] = &["File", "Image"];
+
+// Wikimedia projects all share the same configuration.
+const WIKIMEDIA_API_PATH: &str = "/w/api.php";
+const WIKIMEDIA_ARTICLE_PATH: &str = "/wiki/$1";
+
+pub(crate) fn is_wikimedia_host(hostname: &str) -> bool {
+    if let Some(sub) = hostname.strip_suffix(".wikimedia.org") {
+        let wiki = sub.strip_suffix(".m").unwrap_or(sub);
+        return WIKIMEDIA_ORG_WIKIS.contains(&wiki);
+    }
+
+    PROJECT_DOMAINS.iter().any(|domain| {
+        hostname
+            .strip_suffix(domain)
+            .is_some_and(|sub| sub.is_empty() || sub.ends_with('.'))
+    })
+}
 
 pub(crate) fn process(agent: &Agent, url: &Url) -> Option<anyhow::Result<Content>> {
-    let raw_title = url.path_segments().and_then(|mut s| s.nth(1))?;
+    let title = title_from_url(url, WIKIMEDIA_ARTICLE_PATH)?;
 
     Some((|| {
-        let api_url = url.join("/w/api.php")?;
-        let title = percent_encoding::percent_decode_str(raw_title).decode_utf8()?;
-        let response: Response = agent
-            .get(api_url.as_str())
-            .query_pairs([
-                ("action", "query"),
-                ("format", "json"),
-                ("titles", &title),
-                ("prop", "revisions"),
-                ("rvprop", "content"),
-                ("rvslots", "main"),
-            ])
-            .call()?
-            .body_mut()
-            .read_json()?;
-
-        let mut pages: Vec<_> = response.query.pages.into_values().collect();
-        let Some(mut page) = pages.pop() else {
-            bail!("Unexpected wikimedia pages value {pages:?}");
-        };
+        let api_url = url.join(WIKIMEDIA_API_PATH)?;
+        let mut file_namespace_names: Vec<_> =
+            FILE_NAMESPACE_NAMES.iter().map(|&n| n.to_owned()).collect();
+        // Only the wiki knows its localized names, such as Datei on the German Wikipedia.
+        if title.contains(':') && !is_file_title(&title, &file_namespace_names) {
+            file_namespace_names = get_siteinfo(agent, &api_url)?.file_namespace_names();
+        }
+        process_page(agent, &api_url, &title, url, &file_namespace_names)
+    })())
+}
 
-        let Some(mut revision) = page.revisions.pop() else {
-            bail!("Unexpected wikimedia revisions {:?}", page.revisions);
-        };
+pub(crate) fn try_process(
+    agent: &Agent,
+    url: &Url,
+    tree: &Html,
+) -> Option<anyhow::Result<Content>> {
+    if !html::select_single_element(tree, "meta[name=\"generator\"]")
+        .and_then(|e| e.attr("content"))
+        .is_some_and(|c| c.starts_with("MediaWiki "))
+    {
+        return None;
+    }
 
-        if let Some(slot) = revision.slots.remove("main") {
-            Ok(Content::Text(TextType::Article(Article {
-                title: page.title,
-                body: textwrap::fill(&slot.star, LINE_LENGTH),
-            })))
-        } else {
-            bail!(
-                "Wikimedia revision lacks main slot. {:?}",
-                page.revisions[0].slots
-            );
-        }
+    // The EditURI link is the RSD endpoint, which is api.php with action=rsd.
+    let mut api_url = url
+        .join(html::select_single_element(tree, "link[rel=\"EditURI\"]")?.attr("href")?)
+        .ok()?;
+    api_url.set_query(None);
+
+    Some((|| {
+        let siteinfo = get_siteinfo(agent, &api_url)?;
+        let title = title_from_url(url, &siteinfo.general.articlepath)
+            .context("Unknown MediaWiki URL format")?;
+        let file_namespace_names = siteinfo.file_namespace_names();
+        process_page(agent, &api_url, &title, url, &file_namespace_names)
     })())
 }
 
+fn get_siteinfo(agent: &Agent, api_url: &Url) -> anyhow::Result<SiteInfoQuery> {
+    let siteinfo: QueryResponse<SiteInfoQuery> = agent
+        .get(api_url.as_str())
+        .query_pairs([
+            ("action", "query"),
+            ("format", "json"),
+            ("formatversion", "2"),
+            ("meta", "siteinfo"),
+            ("siprop", "general|namespaces|namespacealiases"),
+        ])
+        .call()?
+        .body_mut()
+        .read_json()?;
+    Ok(siteinfo.query)
+}
+
+/// Extract the page title from `url` given the wiki's `article_path`, in which `$1` stands for
+/// the title.
+fn title_from_url(url: &Url, article_path: &str) -> Option<String> {
+    if let Some((_, title)) = url.query_pairs().find(|(k, _)| k == "title") {
+        return Some(title.into_owned());
+    }
+
+    let (prefix, suffix) = article_path.split_once("$1")?;
+    let encoded_title = url.path().strip_prefix(prefix)?.strip_suffix(suffix)?;
+    if encoded_title.is_empty() {
+        return None;
+    }
+    percent_encoding::percent_decode_str(encoded_title)
+        .decode_utf8()
+        .ok()
+        .map(Into::into)
+}
+
+#[derive(Debug, PartialEq)]
+enum Fragment {
+    /// The media viewer shows the file with this title over the page.
+    Media(String),
+    Section(String),
+}
+
+fn parse_fragment(url: &Url) -> Option<Fragment> {
+    // A fragment that isn't UTF-8 can't name a section or file, so show the whole page.
+    let fragment = percent_encoding::percent_decode_str(url.fragment()?)
+        .decode_utf8()
+        .ok()?;
+    Some(match fragment.strip_prefix("/media/") {
+        Some(file_title) => Fragment::Media(file_title.to_owned()),
+        None => Fragment::Section(fragment.into_owned()),
+    })
+}
+
+/// Return whether `title` is in the file namespace given the names of that namespace.
+fn is_file_title(title: &str, file_namespace_names: &[String]) -> bool {
+    title.split_once(':').is_some_and(|(namespace, _)| {
+        let namespace = namespace.trim().replace('_', " ");
+        file_namespace_names
+            .iter()
+            .any(|n| n.eq_ignore_ascii_case(&namespace))
+    })
+}
+
+fn process_page(
+    agent: &Agent,
+    api_url: &Url,
+    title: &str,
+    url: &Url,
+    file_namespace_names: &[String],
+) -> anyhow::Result<Content> {
+    let fragment = parse_fragment(url);
+
+    let file_title = match &fragment {
+        Some(Fragment::Media(file_title)) => Some(file_title.as_str()),
+        _ => Some(title).filter(|t| is_file_title(t, file_namespace_names)),
+    };
+    if let Some(file_title) = file_title
+        && let Some(file) = get_file_info(agent, api_url, file_title)?
+    {
+        let file_url = Url::parse(&file.url).context("MediaWiki API returned invalid URL")?;
+        return match file.mediatype.as_str() {
+            "AUDIO" => Ok(Content::Audio(file_url)),
+            "MULTIMEDIA" | "VIDEO" => Ok(Content::Video(file_url)),
+            _ => process_generic(agent, &file_url),
+        };
+    }
+
+    let section = if let Some(Fragment::Section(anchor)) = fragment {
+        let parsed: ParsedSections = parse(agent, api_url, title, &[("prop", "sections")])?;
+        parsed
+            .sections
+            .into_iter()
+            .find(|s| s.anchor == anchor)
+            .map(|s| s.index)
+    } else {
+        None
+    };
+
+    let mut params = vec![
+        ("prop", "text"),
+        ("disableeditsection", "1"),
+        ("disabletoc", "1"),
+    ];
+    if let Some(section) = &section {
+        params.push(("section", section));
+    }
+    let parsed: ParsedText = parse(agent, api_url, title, &params)?;
+
+    Ok(Content::Text(TextType::Article(parsed.render(url))))
+}
+
+fn parse<T: DeserializeOwned>(
+    agent: &Agent,
+    api_url: &Url,
+    title: &str,
+    params: &[(&str, &str)],
+) -> anyhow::Result<T> {
+    let response: ParseResponse<T> = agent
+        .get(api_url.as_str())
+        .query_pairs([
+            ("action", "parse"),
+            ("format", "json"),
+            ("formatversion", "2"),
+            ("page", title),
+            ("redirects", "1"),
+        ])
+        .query_pairs(params.iter().copied())
+        .call()?
+        .body_mut()
+        .read_json()?;
+
+    let Some(parsed) = response.parse else {
+        bail!(
+            "MediaWiki API error: {}",
+            response.error.map_or_else(String::new, |e| e.info)
+        );
+    };
+    Ok(parsed)
+}
+
+/// Return the original file of `title` or `None` if `title` is not a file page.
+fn get_file_info(agent: &Agent, api_url: &Url, title: &str) -> anyhow::Result<Option<ImageInfo>> {
+    let response: QueryResponse<PagesQuery> = agent
+        .get(api_url.as_str())
+        .query_pairs([
+            ("action", "query"),
+            ("format", "json"),
+            ("formatversion", "2"),
+            ("iiprop", "url|mediatype"),
+            ("prop", "imageinfo"),
+            ("redirects", "1"),
+            ("titles", title),
+        ])
+        .call()?
+        .body_mut()
+        .read_json()?;
+
+    Ok(response
+        .query
+        .pages
+        .into_iter()
+        .find(|p| p.ns == FILE_NAMESPACE)
+        .and_then(|p| p.imageinfo)
+        .and_then(|mut i| i.pop()))
+}
+
+// Responses carry either parse or error alongside other keys such as warnings.
 #[derive(Debug, Deserialize)]
-struct Response {
-    query: ResponseQuery,
+struct ParseResponse<T> {
+    error: Option<ApiError>,
+    parse: Option<T>,
 }
 
 #[derive(Debug, Deserialize)]
-struct ResponseQuery {
-    pages: HashMap<String, ResponsePage>,
+struct ApiError {
+    info: String,
 }
 
 #[derive(Debug, Deserialize)]
-struct ResponsePage {
+struct ParsedSections {
+    sections: Vec<Section>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ParsedText {
     title: String,
-    revisions: Vec<Revision>,
+    text: String,
+}
+
+impl ParsedText {
+    fn render(self, url: &Url) -> Article {
+        let mut tree = Html::parse_fragment(&self.text);
+        html::remove_elements(&mut tree, CLUTTER_SELECTOR);
+
+        Article {
+            title: self.title,
+            body: html::render_node(*tree.root_element(), url, NonZeroUsize::new(LINE_LENGTH)),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Section {
+    anchor: String,
+    index: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct QueryResponse<T> {
+    query: T,
 }
 
 #[derive(Debug, Deserialize)]
-struct Revision {
-    slots: HashMap<String, Slot>,
+struct PagesQuery {
+    pages: Vec<Page>,
 }
 
 #[derive(Debug, Deserialize)]
-struct Slot {
-    #[serde(rename = "*")]
-    star: String,
+struct Page {
+    ns: i64,
+    imageinfo: Option<Vec<ImageInfo>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ImageInfo {
+    mediatype: String,
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SiteInfoQuery {
+    general: SiteInfoGeneral,
+    namespaces: HashMap<String, Namespace>,
+    namespacealiases: Vec<NamespaceAlias>,
+}
+
+impl SiteInfoQuery {
+    fn file_namespace_names(self) -> Vec<String> {
+        let mut names: Vec<_> = FILE_NAMESPACE_NAMES.iter().map(|&n| n.to_owned()).collect();
+        names.extend(
+            self.namespaces
+                .into_values()
+                .filter(|n| n.id == FILE_NAMESPACE)
+                .map(|n| n.name),
+        );
+        names.extend(
+            self.namespacealiases
+                .into_iter()
+                .filter(|a| a.id == FILE_NAMESPACE)
+                .map(|a| a.alias),
+        );
+        names
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Namespace {
+    id: i64,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct NamespaceAlias {
+    id: i64,
+    alias: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SiteInfoGeneral {
+    articlepath: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use ureq::Agent;
+    use url::Url;
+
+    use super::Fragment;
+    use super::ParsedText;
+    use super::is_file_title;
+    use super::is_wikimedia_host;
+    use super::parse_fragment;
+    use super::process;
+    use super::title_from_url;
+    use crate::Content;
+    use crate::tests::serve_json;
+
+    macro_rules! title_tests {
+        ($(($name: ident, $url: expr, $article_path: expr, $expected: expr),)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let url = Url::parse($url).unwrap();
+                    assert_eq!(title_from_url(&url, $article_path).as_deref(), $expected);
+                }
+            )*
+        }
+    }
+
+    title_tests!(
+        (
+            wiki,
+            "https://en.wikipedia.org/wiki/Rust_(programming_language)",
+            "/wiki/$1",
+            Some("Rust_(programming_language)")
+        ),
+        (
+            encoded,
+            "https://de.wikipedia.org/wiki/K%C3%B6ln",
+            "/wiki/$1",
+            Some("K\u{f6}ln")
+        ),
+        (
+            subpage,
+            "https://www.mediawiki.org/wiki/Help:Links/en",
+            "/wiki/$1",
+            Some("Help:Links/en")
+        ),
+        (
+            query,
+            "https://en.wikipedia.org/w/index.php?title=Rust&action=history",
+            "/wiki/$1",
+            Some("Rust")
+        ),
+        (
+            custom_path,
+            "https://wiki.archlinux.org/title/Pacman",
+            "/title/$1",
+            Some("Pacman")
+        ),
+        (
+            index_php,
+            "https://example.com/index.php/Main_Page",
+            "/index.php/$1",
+            Some("Main_Page")
+        ),
+        (empty, "https://en.wikipedia.org/wiki/", "/wiki/$1", None),
+        (other, "https://en.wikipedia.org/", "/wiki/$1", None),
+    );
+
+    #[test]
+    fn wikimedia_hosts() {
+        assert!(is_wikimedia_host("en.wikipedia.org"));
+        assert!(is_wikimedia_host("de.m.wikipedia.org"));
+        assert!(is_wikimedia_host("en.wiktionary.org"));
+        assert!(is_wikimedia_host("commons.wikimedia.org"));
+        assert!(is_wikimedia_host("meta.m.wikimedia.org"));
+        assert!(!is_wikimedia_host("phabricator.wikimedia.org"));
+        assert!(!is_wikimedia_host("upload.wikimedia.org"));
+        assert!(is_wikimedia_host("www.mediawiki.org"));
+        assert!(!is_wikimedia_host("notwikipedia.org"));
+        assert!(!is_wikimedia_host("wiki.archlinux.org"));
+    }
+
+    #[test]
+    fn fragments() {
+        let fragment = |url| parse_fragment(&Url::parse(url).unwrap());
+        assert_eq!(fragment("https://en.wikipedia.org/wiki/Rust"), None);
+        assert_eq!(
+            fragment("https://en.wikipedia.org/wiki/Rust#History"),
+            Some(Fragment::Section("History".to_owned()))
+        );
+        assert_eq!(
+            fragment("https://de.wikipedia.org/wiki/K%C3%B6ln#Geschichte_K%C3%B6lns"),
+            Some(Fragment::Section("Geschichte_K\u{f6}lns".to_owned()))
+        );
+        assert_eq!(
+            fragment("https://en.wikipedia.org/wiki/Rust#/media/File:Rust_logo.svg"),
+            Some(Fragment::Media("File:Rust_logo.svg".to_owned()))
+        );
+        assert_eq!(fragment("https://en.wikipedia.org/wiki/Rust#%FF"), None);
+    }
+
+    #[test]
+    fn file_titles() {
+        let names = ["File".to_owned(), "Image".to_owned(), "Datei".to_owned()];
+        assert!(is_file_title("File:Rust_logo.svg", &names));
+        assert!(is_file_title("file:Rust_logo.svg", &names));
+        assert!(is_file_title("Datei:K\u{f6}ln.jpg", &names));
+        assert!(!is_file_title("Help:Links", &names));
+        assert!(!is_file_title("Star Wars: Andor", &names));
+        assert!(!is_file_title("Rust", &names));
+    }
+
+    #[test]
+    fn localized_file() {
+        let base = serve_json(vec![
+            (
+                "meta=siteinfo",
+                r#"{"query": {
+                    "general": {"articlepath": "/wiki/$1"},
+                    "namespaces": {
+                        "0": {"id": 0, "name": ""},
+                        "6": {"id": 6, "name": "Datei"}
+                    },
+                    "namespacealiases": [{"id": 6, "alias": "Bild"}]
+                }}"#
+                .to_owned(),
+            ),
+            (
+                "prop=imageinfo",
+                r#"{"query": {"pages": [{
+                    "ns": 6,
+                    "imageinfo": [{"mediatype": "AUDIO", "url": "https://example.com/a.ogg"}]
+                }]}}"#
+                    .to_owned(),
+            ),
+        ]);
+        let url = base.join("/wiki/Datei:K%C3%B6ln.ogg").unwrap();
+        match process(&Agent::new_with_defaults(), &url) {
+            Some(Ok(Content::Audio(audio_url))) => {
+                assert_eq!(audio_url.as_str(), "https://example.com/a.ogg");
+            }
+            _ => panic!("Expected audio"),
+        }
+    }
+
+    #[test]
+    fn render_article() {
+        let parsed = ParsedText {
+            title: "Rust".to_owned(),
+            text: r##"<div class="mw-parser-output"><h2>History<span class="mw-editsection">[edit]</span></h2><p>Rust began<sup class="reference"><a href="#cite_note-1">[1]</a></sup> at <a href="/wiki/Mozilla">Mozilla</a>.</p><div class="navbox">Navigation</div></div>"##
+                .to_owned(),
+        };
+        let article = parsed.render(&Url::parse("https://en.wikipedia.org/wiki/Rust").unwrap());
+        assert_eq!(article.title, "Rust");
+        assert_eq!(
+            article.body,
+            "History\n-------\n\nRust began at [Mozilla](https://en.wikipedia.org/wiki/Mozilla)."
+        );
+    }
 }